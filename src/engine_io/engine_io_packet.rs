@@ -0,0 +1,212 @@
+use base64::Engine;
+
+use crate::{EngineMessage, SocketIoHandshakeOpenModel};
+
+const RECORD_SEPARATOR: char = '\x1e';
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EngineIoDecodeError {
+    EmptyPacket,
+    UnknownPacketType(char),
+    InvalidOpenPacket(String),
+    InvalidBase64(String),
+}
+
+/// Engine.IO packet which is sent over the wire as a single text or binary frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EngineIoFrame {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+/// Engine.IO v4 packet. Message data is not interpreted - Socket.IO layer is built on top of it
+#[derive(Debug, Clone)]
+pub enum EngineIoPacket {
+    Open(SocketIoHandshakeOpenModel),
+    Close,
+    Ping { payload: Option<String> },
+    Pong { payload: Option<String> },
+    Message(EngineMessage),
+    Upgrade,
+    Noop,
+}
+
+impl EngineIoPacket {
+    pub fn deserialize(src: &str) -> Result<Self, EngineIoDecodeError> {
+        let first_char = match src.chars().next() {
+            Some(first_char) => first_char,
+            None => return Err(EngineIoDecodeError::EmptyPacket),
+        };
+
+        let payload = &src[first_char.len_utf8()..];
+
+        let result = match first_char {
+            '0' => {
+                let model = serde_json::from_str(payload)
+                    .map_err(|err| EngineIoDecodeError::InvalidOpenPacket(err.to_string()))?;
+                Self::Open(model)
+            }
+            '1' => Self::Close,
+            '2' => Self::Ping {
+                payload: read_payload(payload),
+            },
+            '3' => Self::Pong {
+                payload: read_payload(payload),
+            },
+            '4' => Self::Message(EngineMessage::Text(payload.to_string())),
+            '5' => Self::Upgrade,
+            '6' => Self::Noop,
+            _ => return Err(EngineIoDecodeError::UnknownPacketType(first_char)),
+        };
+
+        Ok(result)
+    }
+
+    /// Binary frames are always messages
+    pub fn from_binary(data: Vec<u8>) -> Self {
+        Self::Message(EngineMessage::Binary(data))
+    }
+
+    pub fn serialize(&self) -> EngineIoFrame {
+        let mut result = String::new();
+
+        match self {
+            Self::Open(model) => {
+                result.push('0');
+                result.push_str(&serde_json::to_string(model).unwrap());
+            }
+            Self::Close => result.push('1'),
+            Self::Ping { payload } => {
+                result.push('2');
+
+                if let Some(payload) = payload {
+                    result.push_str(payload);
+                }
+            }
+            Self::Pong { payload } => {
+                result.push('3');
+
+                if let Some(payload) = payload {
+                    result.push_str(payload);
+                }
+            }
+            Self::Message(EngineMessage::Text(data)) => {
+                result.push('4');
+                result.push_str(data);
+            }
+            Self::Message(EngineMessage::Binary(data)) => {
+                return EngineIoFrame::Binary(data.clone());
+            }
+            Self::Upgrade => result.push('5'),
+            Self::Noop => result.push('6'),
+        }
+
+        EngineIoFrame::Text(result)
+    }
+}
+
+/// Splits polling request body by record separator. Binary packets are encoded as `b<base64>`
+pub fn decode_polling_payload(body: &str) -> Result<Vec<EngineIoPacket>, EngineIoDecodeError> {
+    let mut result = Vec::new();
+
+    for packet in body.split(RECORD_SEPARATOR) {
+        if packet.is_empty() {
+            continue;
+        }
+
+        if let Some(encoded) = packet.strip_prefix('b') {
+            let data = base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .map_err(|err| EngineIoDecodeError::InvalidBase64(err.to_string()))?;
+
+            result.push(EngineIoPacket::from_binary(data));
+            continue;
+        }
+
+        result.push(EngineIoPacket::deserialize(packet)?);
+    }
+
+    Ok(result)
+}
+
+pub fn encode_polling_frame(frame: &EngineIoFrame) -> String {
+    match frame {
+        EngineIoFrame::Text(text) => text.clone(),
+        EngineIoFrame::Binary(data) => encode_polling_binary(data),
+    }
+}
+
+pub fn encode_polling_binary(data: &[u8]) -> String {
+    let mut result = String::from("b");
+    base64::engine::general_purpose::STANDARD.encode_string(data, &mut result);
+    result
+}
+
+fn read_payload(payload: &str) -> Option<String> {
+    if payload.is_empty() {
+        None
+    } else {
+        Some(payload.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_body_is_not_parsed() {
+        let packet = EngineIoPacket::deserialize(r#"42["unterminated"#).unwrap();
+
+        assert_eq!(
+            packet.serialize(),
+            EngineIoFrame::Text(r#"42["unterminated"#.to_string())
+        );
+
+        match packet {
+            EngineIoPacket::Message(EngineMessage::Text(data)) => {
+                assert_eq!(data, r#"2["unterminated"#)
+            }
+            _ => panic!("Message is expected"),
+        }
+    }
+
+    #[test]
+    fn test_invalid_packets() {
+        assert_eq!(
+            EngineIoPacket::deserialize("").err().unwrap(),
+            EngineIoDecodeError::EmptyPacket
+        );
+        assert_eq!(
+            EngineIoPacket::deserialize("9").err().unwrap(),
+            EngineIoDecodeError::UnknownPacketType('9')
+        );
+        assert!(matches!(
+            EngineIoPacket::deserialize("0{"),
+            Err(EngineIoDecodeError::InvalidOpenPacket(_))
+        ));
+        assert_eq!(
+            EngineIoPacket::deserialize("é").err().unwrap(),
+            EngineIoDecodeError::UnknownPacketType('é')
+        );
+        assert_eq!(
+            decode_polling_payload("2\x1e€uro").err().unwrap(),
+            EngineIoDecodeError::UnknownPacketType('€')
+        );
+    }
+
+    #[test]
+    fn test_polling_payload() {
+        let packets = decode_polling_payload("2probe\x1e4hello\x1ebAQID").unwrap();
+
+        assert_eq!(packets.len(), 3);
+
+        match &packets[0] {
+            EngineIoPacket::Ping { payload } => assert_eq!(payload.as_deref(), Some("probe")),
+            _ => panic!("Ping is expected"),
+        }
+
+        assert_eq!(encode_polling_frame(&packets[1].serialize()), "4hello");
+        assert_eq!(encode_polling_frame(&packets[2].serialize()), "bAQID");
+    }
+}
@@ -0,0 +1,6 @@
+/// Data of Engine.IO message packet (`4`) when Engine.IO is used without Socket.IO on top
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EngineMessage {
+    Text(String),
+    Binary(Vec<u8>),
+}
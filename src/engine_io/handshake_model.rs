@@ -0,0 +1,111 @@
+use std::time::Duration;
+
+use serde::*;
+
+use crate::SocketIoSettings;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SocketIoHandshakeOpenModel {
+    pub sid: String,
+    #[serde(default)]
+    pub upgrades: Vec<String>,
+    /// Milliseconds
+    #[serde(rename = "pingInterval")]
+    pub ping_interval: u64,
+    /// Milliseconds
+    #[serde(rename = "pingTimeout")]
+    pub ping_timeout: u64,
+    /// Is not sent by Engine.IO v3
+    #[serde(
+        rename = "maxPayload",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_payload: Option<u64>,
+    /// Server specific fields which are not part of the protocol
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl SocketIoHandshakeOpenModel {
+    pub fn from_settings(sid: String, socket_io_settings: &SocketIoSettings) -> Self {
+        Self {
+            sid,
+            upgrades: socket_io_settings.upgrades.clone(),
+            ping_interval: socket_io_settings.ping_interval.as_millis() as u64,
+            ping_timeout: socket_io_settings.ping_timeout.as_millis() as u64,
+            max_payload: Some(socket_io_settings.max_payload_size as u64),
+            extra: serde_json::Map::new(),
+        }
+    }
+
+    /// Engine.IO v3 handshake has no `maxPayload`
+    pub fn from_settings_v3(sid: String, socket_io_settings: &SocketIoSettings) -> Self {
+        let mut result = Self::from_settings(sid, socket_io_settings);
+        result.max_payload = None;
+        result
+    }
+
+    pub fn get_ping_interval(&self) -> Duration {
+        Duration::from_millis(self.ping_interval)
+    }
+
+    pub fn get_ping_timeout(&self) -> Duration {
+        Duration::from_millis(self.ping_timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SocketIoContract;
+
+    fn round_trip(model: SocketIoHandshakeOpenModel) -> (String, SocketIoHandshakeOpenModel) {
+        let text_frame = SocketIoContract::Open(model).serialize().text_frame;
+
+        match SocketIoContract::deserialize(&text_frame).unwrap() {
+            SocketIoContract::Open(model) => (text_frame, model),
+            _ => panic!("Open is expected"),
+        }
+    }
+
+    #[test]
+    fn test_v4_with_extra_fields() {
+        let mut model =
+            SocketIoHandshakeOpenModel::from_settings("abc".to_string(), &Default::default());
+        model
+            .extra
+            .insert("region".to_string(), serde_json::json!("eu-1"));
+
+        let (text_frame, model) = round_trip(model);
+
+        assert_eq!(
+            text_frame,
+            r#"0{"sid":"abc","upgrades":["websocket"],"pingInterval":25000,"pingTimeout":20000,"maxPayload":1000000,"region":"eu-1"}"#
+        );
+
+        assert_eq!(model.upgrades, vec!["websocket".to_string()]);
+        assert_eq!(model.get_ping_interval(), Duration::from_secs(25));
+        assert_eq!(model.max_payload, Some(1000000));
+        assert_eq!(model.extra["region"], "eu-1");
+    }
+
+    #[test]
+    fn test_v3_without_max_payload() {
+        let settings = SocketIoSettings {
+            upgrades: vec![],
+            ..Default::default()
+        };
+
+        let model = SocketIoHandshakeOpenModel::from_settings_v3("abc".to_string(), &settings);
+
+        let (text_frame, model) = round_trip(model);
+
+        assert_eq!(
+            text_frame,
+            r#"0{"sid":"abc","upgrades":[],"pingInterval":25000,"pingTimeout":20000}"#
+        );
+        assert!(model.max_payload.is_none());
+        assert!(model.extra.is_empty());
+    }
+}
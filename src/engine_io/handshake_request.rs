@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+
+use crate::{SocketIoHandshakeOpenModel, SocketIoSettings};
+
+pub const ENGINE_IO_PROTOCOL_VERSION: u8 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineIoTransport {
+    Polling,
+    WebSocket,
+    WebTransport,
+}
+
+impl EngineIoTransport {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "polling" => Some(Self::Polling),
+            "websocket" => Some(Self::WebSocket),
+            "webtransport" => Some(Self::WebTransport),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Polling => "polling",
+            Self::WebSocket => "websocket",
+            Self::WebTransport => "webtransport",
+        }
+    }
+}
+
+/// Errors Engine.IO server responds with. Serialized as `{"code":0,"message":"Transport unknown"}`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineIoError {
+    TransportUnknown,
+    UnknownSid,
+    BadHandshakeMethod,
+    BadRequest,
+    Forbidden,
+    UnsupportedProtocolVersion,
+}
+
+impl EngineIoError {
+    pub fn get_code(&self) -> i32 {
+        match self {
+            Self::TransportUnknown => 0,
+            Self::UnknownSid => 1,
+            Self::BadHandshakeMethod => 2,
+            Self::BadRequest => 3,
+            Self::Forbidden => 4,
+            Self::UnsupportedProtocolVersion => 5,
+        }
+    }
+
+    pub fn get_message(&self) -> &'static str {
+        match self {
+            Self::TransportUnknown => "Transport unknown",
+            Self::UnknownSid => "Session ID unknown",
+            Self::BadHandshakeMethod => "Bad handshake method",
+            Self::BadRequest => "Bad request",
+            Self::Forbidden => "Forbidden",
+            Self::UnsupportedProtocolVersion => "Unsupported protocol version",
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+impl Serialize for EngineIoError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("EngineIoError", 2)?;
+        state.serialize_field("code", &self.get_code())?;
+        state.serialize_field("message", self.get_message())?;
+        state.end()
+    }
+}
+
+#[derive(Debug)]
+pub struct HandshakeRequest {
+    pub protocol_version: u8,
+    pub transport: EngineIoTransport,
+    pub sid: Option<String>,
+    pub timestamp: Option<String>,
+    pub query: HashMap<String, String>,
+}
+
+impl HandshakeRequest {
+    /// Accepts either full path with query (`/socket.io/?EIO=4&transport=polling`) or query string only
+    pub fn parse(path_and_query: &str) -> Result<Self, EngineIoError> {
+        let query = match path_and_query.find('?') {
+            Some(index) => &path_and_query[index + 1..],
+            None => path_and_query,
+        };
+
+        Self::from_query_string(query)
+    }
+
+    pub fn from_query_string(query: &str) -> Result<Self, EngineIoError> {
+        let mut protocol_version = None;
+        let mut transport = None;
+        let mut sid = None;
+        let mut timestamp = None;
+        let mut extra = HashMap::new();
+
+        for pair in query.split('&') {
+            if pair.is_empty() {
+                continue;
+            }
+
+            let (key, value) = match pair.find('=') {
+                Some(index) => (&pair[..index], &pair[index + 1..]),
+                None => (pair, ""),
+            };
+
+            let key = decode_query_component(key).ok_or(EngineIoError::BadRequest)?;
+            let value = decode_query_component(value).ok_or(EngineIoError::BadRequest)?;
+
+            match key.as_str() {
+                "EIO" => protocol_version = Some(value),
+                "transport" => transport = Some(value),
+                "sid" => sid = Some(value),
+                "t" => timestamp = Some(value),
+                _ => {
+                    extra.insert(key, value);
+                }
+            }
+        }
+
+        let transport = transport
+            .as_deref()
+            .and_then(EngineIoTransport::parse)
+            .ok_or(EngineIoError::TransportUnknown)?;
+
+        let protocol_version: u8 = protocol_version
+            .and_then(|v| v.parse().ok())
+            .ok_or(EngineIoError::UnsupportedProtocolVersion)?;
+
+        if protocol_version != ENGINE_IO_PROTOCOL_VERSION {
+            return Err(EngineIoError::UnsupportedProtocolVersion);
+        }
+
+        if let Some(sid) = &sid {
+            if sid.is_empty() {
+                return Err(EngineIoError::UnknownSid);
+            }
+        }
+
+        Ok(Self {
+            protocol_version,
+            transport,
+            sid,
+            timestamp,
+            query: extra,
+        })
+    }
+
+    /// Handshake is a request without `sid`. Only GET (or WebSocket upgrade) can start a session.
+    pub fn is_handshake(&self) -> bool {
+        self.sid.is_none()
+    }
+
+    pub fn validate_method(&self, method: &str) -> Result<(), EngineIoError> {
+        if self.is_handshake() && !method.eq_ignore_ascii_case("GET") {
+            return Err(EngineIoError::BadHandshakeMethod);
+        }
+
+        Ok(())
+    }
+
+    pub fn get_query_param(&self, name: &str) -> Option<&str> {
+        self.query.get(name).map(|v| v.as_str())
+    }
+
+    pub fn create_open_model(
+        &self,
+        sid: String,
+        settings: &SocketIoSettings,
+    ) -> SocketIoHandshakeOpenModel {
+        let mut result = SocketIoHandshakeOpenModel::from_settings(sid, settings);
+
+        if self.transport != EngineIoTransport::Polling {
+            result.upgrades.clear();
+        }
+
+        result
+    }
+}
+
+fn decode_query_component(src: &str) -> Option<String> {
+    let src = src.as_bytes();
+    let mut result = Vec::with_capacity(src.len());
+    let mut index = 0;
+
+    while index < src.len() {
+        match src[index] {
+            b'+' => result.push(b' '),
+            b'%' => {
+                let hex = src.get(index + 1..index + 3)?;
+                let hex = std::str::from_utf8(hex).ok()?;
+                result.push(u8::from_str_radix(hex, 16).ok()?);
+                index += 2;
+            }
+            b => result.push(b),
+        }
+
+        index += 1;
+    }
+
+    String::from_utf8(result).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_websocket_request() {
+        let request = HandshakeRequest::parse(
+            "/socket.io/?EIO=4&transport=websocket&sid=abc-1_2&t=OaZ2b1x&token=a%20b",
+        )
+        .unwrap();
+
+        assert_eq!(request.protocol_version, 4);
+        assert_eq!(request.transport, EngineIoTransport::WebSocket);
+        assert_eq!(request.sid.as_deref(), Some("abc-1_2"));
+        assert_eq!(request.timestamp.as_deref(), Some("OaZ2b1x"));
+        assert_eq!(request.get_query_param("token"), Some("a b"));
+        assert!(!request.is_handshake());
+    }
+
+    #[test]
+    fn test_handshake_request() {
+        let request = HandshakeRequest::parse("EIO=4&transport=polling").unwrap();
+
+        assert!(request.is_handshake());
+        assert_eq!(
+            request.validate_method("POST").unwrap_err(),
+            EngineIoError::BadHandshakeMethod
+        );
+        assert!(request.validate_method("GET").is_ok());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            HandshakeRequest::parse("/socket.io/?EIO=4&transport=flash").unwrap_err(),
+            EngineIoError::TransportUnknown
+        );
+
+        assert_eq!(
+            HandshakeRequest::parse("/socket.io/?EIO=3&transport=polling").unwrap_err(),
+            EngineIoError::UnsupportedProtocolVersion
+        );
+
+        assert_eq!(
+            HandshakeRequest::parse("/socket.io/?transport=polling").unwrap_err(),
+            EngineIoError::UnsupportedProtocolVersion
+        );
+
+        assert_eq!(
+            HandshakeRequest::parse("/socket.io/?EIO=4&transport=polling&sid=%zz").unwrap_err(),
+            EngineIoError::BadRequest
+        );
+    }
+
+    #[test]
+    fn test_error_json() {
+        assert_eq!(
+            EngineIoError::TransportUnknown.to_json(),
+            r#"{"code":0,"message":"Transport unknown"}"#
+        );
+
+        assert_eq!(
+            EngineIoError::UnsupportedProtocolVersion.to_json(),
+            r#"{"code":5,"message":"Unsupported protocol version"}"#
+        );
+    }
+}
@@ -0,0 +1,20 @@
+mod engine_io_packet;
+pub use engine_io_packet::*;
+mod engine_message;
+pub use engine_message::*;
+mod handshake_model;
+pub use handshake_model::*;
+mod handshake_request;
+pub use handshake_request::*;
+mod sid_generator;
+pub use sid_generator::*;
+mod session_store;
+pub use session_store::*;
+#[cfg(any(feature = "tokio-ws", feature = "polling", feature = "server"))]
+mod session_sweeper;
+#[cfg(any(feature = "tokio-ws", feature = "polling", feature = "server"))]
+pub use session_sweeper::*;
+#[cfg(feature = "tokio-ws")]
+mod web_socket_engine;
+#[cfg(feature = "tokio-ws")]
+pub use web_socket_engine::*;
@@ -0,0 +1,126 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+pub trait SessionStore<T> {
+    fn insert(&self, sid: String, session: T);
+    /// Returns session and prolongs its expiration
+    fn get(&self, sid: &str) -> Option<T>;
+    fn remove(&self, sid: &str) -> Option<T>;
+    /// Removes sessions which were not accessed during idle timeout and returns them.
+    /// `SessionSweeper` calls it periodically
+    fn remove_expired(&self) -> Vec<(String, T)>;
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+struct SessionStoreItem<T> {
+    session: T,
+    last_access: Instant,
+}
+
+pub struct InMemorySessionStore<T: Clone> {
+    sessions: Mutex<HashMap<String, SessionStoreItem<T>>>,
+    idle_timeout: Duration,
+}
+
+impl<T: Clone> InMemorySessionStore<T> {
+    pub fn new(idle_timeout: Duration) -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+            idle_timeout,
+        }
+    }
+
+    pub fn get_idle_timeout(&self) -> Duration {
+        self.idle_timeout
+    }
+
+    pub fn remove_expired_at(&self, now: Instant) -> Vec<(String, T)> {
+        let mut sessions = self.sessions.lock().unwrap();
+
+        let expired: Vec<String> = sessions
+            .iter()
+            .filter(|(_, item)| now.duration_since(item.last_access) >= self.idle_timeout)
+            .map(|(sid, _)| sid.clone())
+            .collect();
+
+        expired
+            .into_iter()
+            .filter_map(|sid| {
+                let item = sessions.remove(&sid)?;
+                Some((sid, item.session))
+            })
+            .collect()
+    }
+}
+
+impl<T: Clone> SessionStore<T> for InMemorySessionStore<T> {
+    fn insert(&self, sid: String, session: T) {
+        let item = SessionStoreItem {
+            session,
+            last_access: Instant::now(),
+        };
+
+        self.sessions.lock().unwrap().insert(sid, item);
+    }
+
+    fn get(&self, sid: &str) -> Option<T> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let item = sessions.get_mut(sid)?;
+        item.last_access = Instant::now();
+        Some(item.session.clone())
+    }
+
+    fn remove(&self, sid: &str) -> Option<T> {
+        let item = self.sessions.lock().unwrap().remove(sid)?;
+        Some(item.session)
+    }
+
+    fn remove_expired(&self) -> Vec<(String, T)> {
+        self.remove_expired_at(Instant::now())
+    }
+
+    fn len(&self) -> usize {
+        self.sessions.lock().unwrap().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_get_remove() {
+        let store = InMemorySessionStore::new(Duration::from_secs(10));
+
+        store.insert("sid-1".to_string(), 1);
+        store.insert("sid-2".to_string(), 2);
+
+        assert_eq!(store.get("sid-1"), Some(1));
+        assert_eq!(store.remove("sid-2"), Some(2));
+        assert_eq!(store.get("sid-2"), None);
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn test_idle_sessions_are_removed() {
+        let store = InMemorySessionStore::new(Duration::from_secs(10));
+
+        store.insert("sid-1".to_string(), 1);
+        store.insert("sid-2".to_string(), 2);
+
+        let now = Instant::now();
+
+        assert!(store.remove_expired_at(now).is_empty());
+
+        let expired = store.remove_expired_at(now + Duration::from_secs(11));
+        assert_eq!(expired.len(), 2);
+        assert!(store.is_empty());
+    }
+}
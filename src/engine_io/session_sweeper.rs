@@ -0,0 +1,96 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::task::JoinHandle;
+
+use crate::SessionStore;
+
+/// Background task removing idle sessions every `interval`. Stopped by [`Self::stop`] or on drop
+pub struct SessionSweeper {
+    handle: JoinHandle<()>,
+}
+
+impl SessionSweeper {
+    /// Must be called within tokio runtime
+    pub fn spawn(interval: Duration, sweep: impl Fn() + Send + 'static) -> Self {
+        let handle = tokio::spawn(async move {
+            let mut ticks =
+                tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+
+            loop {
+                ticks.tick().await;
+                sweep();
+            }
+        });
+
+        Self { handle }
+    }
+
+    /// Expired sessions are passed to `on_expired`
+    pub fn for_store<S, T>(
+        store: Arc<S>,
+        interval: Duration,
+        on_expired: impl Fn(String, T) + Send + 'static,
+    ) -> Self
+    where
+        S: SessionStore<T> + Send + Sync + 'static,
+        T: 'static,
+    {
+        Self::spawn(interval, move || {
+            for (sid, session) in store.remove_expired() {
+                on_expired(sid, session);
+            }
+        })
+    }
+
+    pub fn stop(&self) {
+        self.handle.abort();
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.handle.is_finished()
+    }
+}
+
+impl Drop for SessionSweeper {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::InMemorySessionStore;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_sweeps_by_interval_until_stopped() {
+        // Sessions are idle right away, so only the sweep interval matters
+        let store = Arc::new(InMemorySessionStore::new(Duration::ZERO));
+        let expired = Arc::new(Mutex::new(Vec::new()));
+
+        let expired_copy = expired.clone();
+        let sweeper = SessionSweeper::for_store(
+            store.clone(),
+            Duration::from_secs(10),
+            move |sid, session: i32| expired_copy.lock().unwrap().push((sid, session)),
+        );
+
+        store.insert("sid-1".to_string(), 1);
+
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        assert_eq!(store.len(), 1);
+
+        tokio::time::sleep(Duration::from_secs(6)).await;
+        assert!(store.is_empty());
+        assert_eq!(*expired.lock().unwrap(), vec![("sid-1".to_string(), 1)]);
+
+        sweeper.stop();
+        store.insert("sid-2".to_string(), 2);
+
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        assert_eq!(store.len(), 1);
+        assert!(sweeper.is_stopped());
+    }
+}
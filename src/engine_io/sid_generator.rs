@@ -0,0 +1,42 @@
+use base64::Engine;
+
+pub trait SidGenerator {
+    fn generate_sid(&self) -> String;
+}
+
+/// Same format as Node.js `base64id`: 15 random bytes encoded as 20 characters of url-safe base64
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RandomSidGenerator;
+
+impl SidGenerator for RandomSidGenerator {
+    fn generate_sid(&self) -> String {
+        let mut bytes = [0u8; 15];
+        getrandom::fill(&mut bytes).expect("Can not get random bytes from OS");
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_random_sid_format() {
+        let generator = RandomSidGenerator;
+
+        let mut generated = HashSet::new();
+
+        for _ in 0..100 {
+            let sid = generator.generate_sid();
+
+            assert_eq!(sid.len(), 20);
+            assert!(sid
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+
+            assert!(generated.insert(sid));
+        }
+    }
+}
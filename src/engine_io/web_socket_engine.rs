@@ -0,0 +1,109 @@
+use futures_util::{SinkExt, StreamExt};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_tungstenite::{tungstenite::Message, WebSocketStream};
+
+use crate::{EngineIoDecodeError, EngineIoFrame, EngineIoPacket};
+
+#[derive(Debug)]
+pub enum EngineIoWebSocketError {
+    WebSocket(tokio_tungstenite::tungstenite::Error),
+    Decode(EngineIoDecodeError),
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for EngineIoWebSocketError {
+    fn from(value: tokio_tungstenite::tungstenite::Error) -> Self {
+        Self::WebSocket(value)
+    }
+}
+
+impl From<EngineIoDecodeError> for EngineIoWebSocketError {
+    fn from(value: EngineIoDecodeError) -> Self {
+        Self::Decode(value)
+    }
+}
+
+/// Engine.IO packets over WebSocket. Each packet is a single text or binary frame
+pub struct EngineIoWebSocket<S> {
+    stream: WebSocketStream<S>,
+    closed: bool,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> EngineIoWebSocket<S> {
+    pub fn new(stream: WebSocketStream<S>) -> Self {
+        Self {
+            stream,
+            closed: false,
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// `None` means connection is closed.
+    pub async fn receive(&mut self) -> Result<Option<EngineIoPacket>, EngineIoWebSocketError> {
+        if self.closed {
+            return Ok(None);
+        }
+
+        while let Some(message) = self.stream.next().await {
+            match message? {
+                Message::Text(text) => {
+                    return Ok(Some(EngineIoPacket::deserialize(text.as_str())?));
+                }
+                Message::Binary(data) => {
+                    return Ok(Some(EngineIoPacket::from_binary(data.to_vec())));
+                }
+                Message::Close(_) => {
+                    self.closed = true;
+                    // Close reply is queued by tungstenite; flushing delivers it to the peer
+                    let _ = self.stream.flush().await;
+                    return Ok(None);
+                }
+                _ => {}
+            }
+        }
+
+        self.closed = true;
+        Ok(None)
+    }
+
+    /// Frames are flushed together after the last one
+    pub async fn send_frames(
+        &mut self,
+        frames: impl IntoIterator<Item = EngineIoFrame>,
+    ) -> Result<(), EngineIoWebSocketError> {
+        for frame in frames {
+            let message = match frame {
+                EngineIoFrame::Text(text) => Message::Text(text.into()),
+                EngineIoFrame::Binary(data) => Message::Binary(data.into()),
+            };
+
+            self.stream.feed(message).await?;
+        }
+
+        self.stream.flush().await?;
+        Ok(())
+    }
+
+    pub async fn send_packet(
+        &mut self,
+        packet: &EngineIoPacket,
+    ) -> Result<(), EngineIoWebSocketError> {
+        self.send_frames([packet.serialize()]).await
+    }
+
+    pub async fn close(&mut self) -> Result<(), EngineIoWebSocketError> {
+        if self.closed {
+            return Ok(());
+        }
+
+        self.closed = true;
+        self.stream.close(None).await?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> WebSocketStream<S> {
+        self.stream
+    }
+}
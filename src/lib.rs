@@ -1,16 +1,15 @@
 //pub mod my_socket_io_messages;
 mod socket_io_settings;
 pub use socket_io_settings::SocketIoSettings;
-mod socket_io_contract;
-pub use socket_io_contract::*;
-mod socket_io_message;
-pub use socket_io_message::*;
 
-mod socket_io_data;
-pub use socket_io_data::*;
-mod payload_deserializer;
-pub use payload_deserializer::*;
-mod payload_serializer;
-pub use payload_serializer::*;
-mod handshake_model;
-pub use handshake_model::*;
+/// Engine.IO protocol: packets, handshake, sessions and transports
+pub mod engine_io;
+pub use engine_io::*;
+/// Socket.IO protocol built on top of Engine.IO
+pub mod socket_io;
+pub use socket_io::*;
+/// Server side sockets and event handling
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "server")]
+pub use server::*;
@@ -0,0 +1,131 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::{
+    serialize_event_params, SocketIoEventParameter, SocketIoMessage, SocketIoSocket,
+    SocketSendError,
+};
+
+use super::socket::serialize_args;
+
+/// Replies to an acknowledged event. Can be used once; if dropped unused - empty ack is sent.
+/// Ack is always sent to the namespace the event came from
+pub struct AckSender {
+    socket: Arc<SocketIoSocket>,
+    namespace: String,
+    ack: i64,
+    sent: bool,
+}
+
+impl AckSender {
+    pub(crate) fn new(socket: Arc<SocketIoSocket>, namespace: String, ack: i64) -> Self {
+        Self {
+            socket,
+            namespace,
+            ack,
+            sent: false,
+        }
+    }
+
+    pub fn get_ack_id(&self) -> i64 {
+        self.ack
+    }
+
+    pub fn get_namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Args are serialized as a single ack argument
+    pub fn send<T: Serialize + ?Sized>(mut self, args: &T) -> Result<(), SocketSendError> {
+        self.send_data(serialize_args(args), Vec::new())
+    }
+
+    /// Several arguments or binary data
+    pub fn send_params(
+        mut self,
+        params: Vec<SocketIoEventParameter>,
+    ) -> Result<(), SocketSendError> {
+        let (data, attachments) = serialize_event_params(params);
+        self.send_data(data, attachments)
+    }
+
+    fn send_data(
+        &mut self,
+        data: String,
+        attachments: Vec<Vec<u8>>,
+    ) -> Result<(), SocketSendError> {
+        self.sent = true;
+
+        let namespace = self.namespace.clone().into();
+
+        let message = if attachments.is_empty() {
+            SocketIoMessage::Ack {
+                namespace,
+                event_name: "".into(),
+                data: data.into(),
+                rest_args: Vec::new(),
+                ack: self.ack,
+            }
+        } else {
+            SocketIoMessage::BinaryAck {
+                namespace,
+                event_name: "".into(),
+                data: data.into(),
+                rest_args: Vec::new(),
+                ack: self.ack,
+                attachments,
+            }
+        };
+
+        self.socket.send_message(&message)
+    }
+}
+
+impl Drop for AckSender {
+    fn drop(&mut self) {
+        if !self.sent {
+            let _ = self.send_data(String::new(), Vec::new());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OutgoingQueue;
+
+    #[test]
+    fn test_send_and_drop() {
+        let queue = Arc::new(OutgoingQueue::new());
+        let socket = Arc::new(SocketIoSocket::new(
+            "sid".to_string(),
+            "/chat".to_string(),
+            queue.clone(),
+        ));
+
+        let ack = AckSender::new(socket.clone(), "/chat".to_string(), 5);
+        ack.send(&serde_json::json!({"ok": true})).unwrap();
+
+        let ack = AckSender::new(socket.clone(), "/chat".to_string(), 6);
+        ack.send_params(vec![SocketIoEventParameter::Binary(vec![1, 2])])
+            .unwrap();
+
+        drop(AckSender::new(socket, "/chat".to_string(), 7));
+
+        assert_eq!(
+            queue.try_pop().unwrap().payload.text_frame,
+            r#"43/chat,5[{"ok":true}]"#
+        );
+
+        let payload = queue.try_pop().unwrap().payload;
+        assert_eq!(
+            payload.text_frame,
+            r#"461-/chat,6[{"_placeholder":true,"num":0}]"#
+        );
+        assert_eq!(payload.binary_frames, vec![vec![1, 2]]);
+
+        assert_eq!(queue.try_pop().unwrap().payload.text_frame, "43/chat,7[]");
+        assert!(queue.try_pop().is_none());
+    }
+}
@@ -0,0 +1,462 @@
+use std::{future::Future, pin::Pin};
+
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use crate::{AckError, SocketDescriptor, SocketIoMessage, SocketIoNamespace, SocketIoSocket};
+
+pub type AdapterFuture<'s, T> = Pin<Box<dyn Future<Output = T> + Send + 's>>;
+
+/// Sockets targeted by an adapter operation. Serializable to be passed to other nodes
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SocketsSelector {
+    /// Socket has to be in any of the rooms. Empty means all the sockets of namespace
+    pub rooms: Vec<String>,
+    /// Sockets in any of these rooms are skipped. Socket id is a room as well
+    pub except: Vec<String>,
+}
+
+impl SocketsSelector {
+    pub fn matches(&self, socket: &SocketIoSocket) -> bool {
+        if self.except.iter().any(|room| socket.is_in_room(room)) {
+            return false;
+        }
+
+        self.rooms.is_empty() || self.rooms.iter().any(|room| socket.is_in_room(room))
+    }
+}
+
+/// Performs namespace wide operations. Cluster adapters forward them to the other nodes
+/// and apply operations received from other nodes with `local_*` methods of [`SocketIoNamespace`]
+pub trait SocketIoAdapter: Send + Sync {
+    fn fetch_sockets<'s>(
+        &'s self,
+        namespace: &'s SocketIoNamespace,
+        selector: &'s SocketsSelector,
+    ) -> AdapterFuture<'s, Vec<SocketDescriptor>>;
+
+    fn add_sockets<'s>(
+        &'s self,
+        namespace: &'s SocketIoNamespace,
+        selector: &'s SocketsSelector,
+        rooms: &'s [String],
+    ) -> AdapterFuture<'s, ()>;
+
+    fn del_sockets<'s>(
+        &'s self,
+        namespace: &'s SocketIoNamespace,
+        selector: &'s SocketsSelector,
+        rooms: &'s [String],
+    ) -> AdapterFuture<'s, ()>;
+
+    fn disconnect_sockets<'s>(
+        &'s self,
+        namespace: &'s SocketIoNamespace,
+        selector: &'s SocketsSelector,
+        close: bool,
+    ) -> AdapterFuture<'s, ()>;
+
+    /// Passes [`SocketIoMessage::Event`] to the other nodes, not to the sockets. The nodes handle it
+    /// with [`SocketIoNamespace::local_server_side_emit`]. With `deadline` the message has ack:
+    /// one result per other node, its `Ack` response or [`AckError::Timeout`] if it has not answered
+    /// by the deadline. The namespace stops waiting for the adapter at the deadline
+    fn server_side_emit<'s>(
+        &'s self,
+        namespace: &'s SocketIoNamespace,
+        message: &'s SocketIoMessage,
+        deadline: Option<Instant>,
+    ) -> AdapterFuture<'s, Vec<Result<SocketIoMessage, AckError>>>;
+}
+
+/// Single node adapter: operations apply to the sockets of this process only
+pub struct LocalAdapter;
+
+impl SocketIoAdapter for LocalAdapter {
+    fn fetch_sockets<'s>(
+        &'s self,
+        namespace: &'s SocketIoNamespace,
+        selector: &'s SocketsSelector,
+    ) -> AdapterFuture<'s, Vec<SocketDescriptor>> {
+        Box::pin(async move { namespace.local_fetch_sockets(selector) })
+    }
+
+    fn add_sockets<'s>(
+        &'s self,
+        namespace: &'s SocketIoNamespace,
+        selector: &'s SocketsSelector,
+        rooms: &'s [String],
+    ) -> AdapterFuture<'s, ()> {
+        Box::pin(async move { namespace.local_add_sockets(selector, rooms) })
+    }
+
+    fn del_sockets<'s>(
+        &'s self,
+        namespace: &'s SocketIoNamespace,
+        selector: &'s SocketsSelector,
+        rooms: &'s [String],
+    ) -> AdapterFuture<'s, ()> {
+        Box::pin(async move { namespace.local_del_sockets(selector, rooms) })
+    }
+
+    fn disconnect_sockets<'s>(
+        &'s self,
+        namespace: &'s SocketIoNamespace,
+        selector: &'s SocketsSelector,
+        close: bool,
+    ) -> AdapterFuture<'s, ()> {
+        Box::pin(namespace.local_disconnect_sockets(selector, close))
+    }
+
+    /// There are no other nodes
+    fn server_side_emit<'s>(
+        &'s self,
+        _namespace: &'s SocketIoNamespace,
+        _message: &'s SocketIoMessage,
+        _deadline: Option<Instant>,
+    ) -> AdapterFuture<'s, Vec<Result<SocketIoMessage, AckError>>> {
+        Box::pin(async { Vec::new() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc, time::Duration};
+
+    use futures_util::future::join_all;
+    use serde::de::DeserializeOwned;
+
+    use super::*;
+    use crate::{
+        AckError, EventRouter, SocketIoConnection, SocketIoContract, SocketIoPayload,
+        SocketIoServer, SocketIoSettings,
+    };
+
+    /// Applies operations to this node and to namespaces of other nodes, encoded like on the wire
+    struct PeersAdapter {
+        peers: Vec<Arc<SocketIoNamespace>>,
+        /// Answers to earlier emits arriving late, as text frames
+        late_responses: Vec<&'static str>,
+        /// Waits for the peers past the deadline, like a stuck cluster transport
+        ignores_deadline: bool,
+    }
+
+    fn over_wire<T: Serialize + DeserializeOwned>(value: &T) -> T {
+        serde_json::from_str(&serde_json::to_string(value).unwrap()).unwrap()
+    }
+
+    impl SocketIoAdapter for PeersAdapter {
+        fn fetch_sockets<'s>(
+            &'s self,
+            namespace: &'s SocketIoNamespace,
+            selector: &'s SocketsSelector,
+        ) -> AdapterFuture<'s, Vec<SocketDescriptor>> {
+            Box::pin(async move {
+                let mut result = namespace.local_fetch_sockets(selector);
+
+                for peer in &self.peers {
+                    let sockets = peer.local_fetch_sockets(&over_wire(selector));
+                    result.extend(over_wire(&sockets));
+                }
+
+                result
+            })
+        }
+
+        fn add_sockets<'s>(
+            &'s self,
+            namespace: &'s SocketIoNamespace,
+            selector: &'s SocketsSelector,
+            rooms: &'s [String],
+        ) -> AdapterFuture<'s, ()> {
+            Box::pin(async move {
+                namespace.local_add_sockets(selector, rooms);
+
+                for peer in &self.peers {
+                    peer.local_add_sockets(&over_wire(selector), &over_wire(&rooms.to_vec()));
+                }
+            })
+        }
+
+        fn del_sockets<'s>(
+            &'s self,
+            namespace: &'s SocketIoNamespace,
+            selector: &'s SocketsSelector,
+            rooms: &'s [String],
+        ) -> AdapterFuture<'s, ()> {
+            Box::pin(async move {
+                namespace.local_del_sockets(selector, rooms);
+
+                for peer in &self.peers {
+                    peer.local_del_sockets(&over_wire(selector), &over_wire(&rooms.to_vec()));
+                }
+            })
+        }
+
+        fn disconnect_sockets<'s>(
+            &'s self,
+            namespace: &'s SocketIoNamespace,
+            selector: &'s SocketsSelector,
+            close: bool,
+        ) -> AdapterFuture<'s, ()> {
+            Box::pin(async move {
+                namespace.local_disconnect_sockets(selector, close).await;
+
+                for peer in &self.peers {
+                    peer.local_disconnect_sockets(&over_wire(selector), close)
+                        .await;
+                }
+            })
+        }
+
+        fn server_side_emit<'s>(
+            &'s self,
+            _namespace: &'s SocketIoNamespace,
+            message: &'s SocketIoMessage,
+            deadline: Option<Instant>,
+        ) -> AdapterFuture<'s, Vec<Result<SocketIoMessage, AckError>>> {
+            let mut payload = SocketIoPayload::new();
+            message.serialize(&mut payload);
+
+            Box::pin(async move {
+                let responses = join_all(self.peers.iter().map(|peer| {
+                    let message = SocketIoMessage::deserialize(&payload.text_frame).unwrap();
+                    let response = peer.local_server_side_emit(message);
+
+                    async move {
+                        match deadline {
+                            Some(deadline) if !self.ignores_deadline => {
+                                tokio::time::timeout_at(deadline, response).await
+                            }
+                            _ => Ok(response.await),
+                        }
+                    }
+                }))
+                .await;
+
+                let mut result = Vec::new();
+
+                for response in responses {
+                    match response {
+                        Ok(Ok(Some(response))) => {
+                            let mut response_payload = SocketIoPayload::new();
+                            response.serialize(&mut response_payload);
+                            result.push(Ok(SocketIoMessage::deserialize(
+                                &response_payload.text_frame,
+                            )
+                            .unwrap()));
+                        }
+                        Err(_) => result.push(Err(AckError::Timeout)),
+                        _ => {}
+                    }
+                }
+
+                for response in &self.late_responses {
+                    result.push(Ok(SocketIoMessage::deserialize(response).unwrap()));
+                }
+
+                result
+            })
+        }
+    }
+
+    fn create_peer(name: &'static str, delay: Duration) -> Arc<SocketIoNamespace> {
+        let mut router = EventRouter::new("/");
+        router.on_server_side("invalidate", move |key: String| async move {
+            tokio::time::sleep(delay).await;
+            format!("{}:{}", name, key)
+        });
+
+        Arc::new(SocketIoNamespace::new(router))
+    }
+
+    #[tokio::test]
+    async fn test_server_side_emit() {
+        let mut router = EventRouter::new("/");
+        router.on_server_side("invalidate", |_: String| async { 42 });
+        let malformed_peer = Arc::new(SocketIoNamespace::new(router));
+
+        let namespace = SocketIoNamespace::new_with_adapter(
+            EventRouter::new("/"),
+            Arc::new(PeersAdapter {
+                peers: vec![
+                    create_peer("b", Duration::ZERO),
+                    create_peer("c", Duration::from_secs(5)),
+                    create_peer("d", Duration::ZERO),
+                    malformed_peer,
+                ],
+                late_responses: vec![r#"399["stale"]"#],
+                ignores_deadline: false,
+            }),
+        );
+
+        // "c" does not answer in time, the late response is to another emit
+        let responses: Vec<Result<String, AckError>> = namespace
+            .server_side_emit_with_ack("invalidate", "key", Duration::from_millis(50))
+            .await;
+
+        assert_eq!(responses.len(), 4);
+        assert_eq!(responses[0], Ok("b:key".to_string()));
+        assert_eq!(responses[1], Err(AckError::Timeout));
+        assert_eq!(responses[2], Ok("d:key".to_string()));
+        assert!(matches!(responses[3], Err(AckError::InvalidArguments(_))));
+
+        let local = SocketIoNamespace::new(EventRouter::new("/"));
+        let responses: Vec<Result<String, AckError>> = local
+            .server_side_emit_with_ack("invalidate", "key", Duration::from_millis(50))
+            .await;
+
+        assert!(responses.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_server_side_emit_empty_ack() {
+        let namespace = SocketIoNamespace::new_with_adapter(
+            EventRouter::new("/"),
+            Arc::new(PeersAdapter {
+                peers: Vec::new(),
+                late_responses: vec!["30[]"],
+                ignores_deadline: false,
+            }),
+        );
+
+        let responses: Vec<Result<(), AckError>> = namespace
+            .server_side_emit_with_ack("invalidate", "key", Duration::from_millis(50))
+            .await;
+
+        assert_eq!(responses, vec![Ok(())]);
+    }
+
+    #[tokio::test]
+    async fn test_server_side_emit_adapter_past_deadline() {
+        let namespace = SocketIoNamespace::new_with_adapter(
+            EventRouter::new("/"),
+            Arc::new(PeersAdapter {
+                peers: vec![create_peer("c", Duration::from_secs(5))],
+                late_responses: Vec::new(),
+                ignores_deadline: true,
+            }),
+        );
+
+        let responses: Vec<Result<String, AckError>> = namespace
+            .server_side_emit_with_ack("invalidate", "key", Duration::from_millis(50))
+            .await;
+
+        assert_eq!(responses, vec![Err(AckError::Timeout)]);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_server_side() {
+        let peer = create_peer("b", Duration::ZERO);
+
+        let response = peer
+            .local_server_side_emit(
+                SocketIoMessage::deserialize(r#"21["invalidate","key"]"#).unwrap(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+
+        let mut payload = SocketIoPayload::new();
+        response.serialize(&mut payload);
+        assert_eq!(payload.text_frame, r#"31["b:key"]"#);
+
+        let response = peer
+            .local_server_side_emit(
+                SocketIoMessage::deserialize(r#"2["invalidate","key"]"#).unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(response.is_none());
+
+        let err = peer
+            .local_server_side_emit(SocketIoMessage::deserialize(r#"2["unknown"]"#).unwrap())
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(
+            err,
+            crate::EventRouterError::UnknownEvent("unknown".to_string())
+        );
+    }
+
+    async fn connect_user(server: &SocketIoServer, sid: &str) -> Arc<SocketIoConnection> {
+        let connection = server.create_connection_with_request(
+            sid.to_string(),
+            HashMap::from([("EIO".to_string(), "4".to_string())]),
+            HashMap::from([("user-agent".to_string(), format!("agent-{}", sid))]),
+        );
+
+        let connect = format!(r#"0{{"user":"{}"}}"#, sid);
+        server
+            .handle_contract(
+                &connection,
+                SocketIoContract::Message(SocketIoMessage::deserialize(&connect).unwrap()),
+            )
+            .await;
+
+        connection.get_socket("/").unwrap().join("users");
+        connection
+    }
+
+    #[tokio::test]
+    async fn test_sockets_operations_across_nodes() {
+        let remote_server = SocketIoServer::new(SocketIoSettings::default());
+        let remote_namespace = remote_server.add_namespace(EventRouter::new("/"));
+
+        let local_server =
+            SocketIoServer::new(SocketIoSettings::default()).with_adapter(PeersAdapter {
+                peers: vec![remote_namespace.clone()],
+                late_responses: Vec::new(),
+                ignores_deadline: false,
+            });
+        let local_namespace = local_server.add_namespace(EventRouter::new("/"));
+
+        let local_connection = connect_user(&local_server, "local").await;
+        let remote_connection = connect_user(&remote_server, "remote").await;
+        let local_socket = local_connection.get_socket("/").unwrap();
+        let remote_socket = remote_connection.get_socket("/").unwrap();
+
+        let mut sockets = local_namespace.in_room("users").fetch_sockets().await;
+        sockets.sort_by(|a, b| a.handshake.sid.cmp(&b.handshake.sid));
+
+        assert_eq!(sockets.len(), 2);
+        assert_eq!(sockets[1].id, remote_socket.get_id());
+        assert_eq!(sockets[1].handshake, *remote_socket.get_handshake());
+        assert_eq!(sockets[1].handshake.query["EIO"], "4");
+        assert_eq!(sockets[1].handshake.headers["user-agent"], "agent-remote");
+        assert_eq!(
+            sockets[1].handshake.auth,
+            serde_json::json!({"user": "remote"})
+        );
+
+        local_namespace
+            .in_room("users")
+            .sockets_join(&["admins"])
+            .await;
+        assert!(local_socket.is_in_room("admins"));
+        assert!(remote_socket.is_in_room("admins"));
+
+        local_namespace
+            .in_room("admins")
+            .except(local_socket.get_id())
+            .sockets_leave(&["users"])
+            .await;
+        assert!(local_socket.is_in_room("users"));
+        assert!(!remote_socket.is_in_room("users"));
+
+        local_namespace
+            .in_room("admins")
+            .disconnect_sockets(false)
+            .await;
+
+        assert_eq!(local_namespace.sockets_count(), 0);
+        assert_eq!(remote_namespace.sockets_count(), 0);
+        assert!(remote_connection.get_socket("/").is_none());
+
+        let mut remote_outgoing = Vec::new();
+        while let Some(outgoing) = remote_connection.get_queue().try_pop() {
+            remote_outgoing.push(outgoing.payload.text_frame);
+        }
+        assert_eq!(remote_outgoing.last().unwrap(), "41");
+    }
+}
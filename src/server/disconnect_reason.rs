@@ -0,0 +1,30 @@
+/// Why a socket left its namespace. Values match Socket.IO reasons
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DisconnectReason {
+    /// Socket was disconnected by the server with [`crate::SocketIoServer::disconnect`]
+    ServerNamespaceDisconnect,
+    /// Client sent namespace `Disconnect`
+    ClientNamespaceDisconnect,
+    /// Whole connection was closed by the server or the client was too slow to read
+    ForcedServerClose,
+    PingTimeout,
+    TransportClose,
+    TransportError,
+    ParseError,
+    ServerShuttingDown,
+}
+
+impl DisconnectReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ServerNamespaceDisconnect => "server namespace disconnect",
+            Self::ClientNamespaceDisconnect => "client namespace disconnect",
+            Self::ForcedServerClose => "forced server close",
+            Self::PingTimeout => "ping timeout",
+            Self::TransportClose => "transport close",
+            Self::TransportError => "transport error",
+            Self::ParseError => "parse error",
+            Self::ServerShuttingDown => "server shutting down",
+        }
+    }
+}
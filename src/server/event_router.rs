@@ -0,0 +1,502 @@
+use std::{collections::HashMap, future::Future, pin::Pin, sync::Arc};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{AckSender, DisconnectReason, SocketIoMessage, SocketIoSocket};
+
+use super::socket::serialize_args;
+
+pub type HandlerFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+type EventHandler = Box<
+    dyn Fn(
+            Arc<SocketIoSocket>,
+            &SocketIoEvent,
+            Option<AckSender>,
+        ) -> Result<HandlerFuture, serde_json::Error>
+        + Send
+        + Sync,
+>;
+
+type AnyEventHandler =
+    Box<dyn Fn(Arc<SocketIoSocket>, SocketIoEvent) -> HandlerFuture + Send + Sync>;
+
+type FallbackHandler = Box<
+    dyn Fn(Arc<SocketIoSocket>, SocketIoEvent, Option<AckSender>) -> HandlerFuture + Send + Sync,
+>;
+
+type ConnectHandler = Box<dyn Fn(Arc<SocketIoSocket>) -> HandlerFuture + Send + Sync>;
+
+type DisconnectHandler =
+    Box<dyn Fn(Arc<SocketIoSocket>, DisconnectReason) -> HandlerFuture + Send + Sync>;
+
+/// Resolves to serialized response
+type ServerSideFuture = Pin<Box<dyn Future<Output = String> + Send>>;
+
+type ServerSideHandler =
+    Box<dyn Fn(&SocketIoEvent) -> Result<ServerSideFuture, serde_json::Error> + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventRouterError {
+    NotAnEvent,
+    NamespaceMismatch { expected: String, actual: String },
+    UnknownEvent(String),
+    InvalidArguments { event_name: String, message: String },
+}
+
+/// Incoming event with arguments as comma separated raw JSON
+#[derive(Debug, Clone)]
+pub struct SocketIoEvent {
+    pub event_name: String,
+    pub data: String,
+    pub ack: Option<i64>,
+    pub attachments: Vec<Vec<u8>>,
+}
+
+impl SocketIoEvent {
+    pub fn from_message(message: SocketIoMessage) -> Option<Self> {
+        let data = message.get_args_data()?.into_owned();
+
+        match message {
+            SocketIoMessage::Event {
+                event_name, ack, ..
+            } => Some(Self {
+                event_name: event_name.as_str().to_string(),
+                data,
+                ack,
+                attachments: Vec::new(),
+            }),
+            SocketIoMessage::BinaryEvent {
+                event_name,
+                ack,
+                attachments,
+                ..
+            } => Some(Self {
+                event_name: event_name.as_str().to_string(),
+                data,
+                ack,
+                attachments,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Single argument is deserialized as is, several arguments - as an array (tuple), no arguments - as `null`.
+    /// Binary placeholders are replaced with attachment bytes
+    pub fn get_args<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        deserialize_args(&self.data, &self.attachments)
+    }
+}
+
+/// Dispatches events of one namespace to handlers registered by event name
+pub struct EventRouter {
+    namespace: String,
+    handlers: HashMap<String, EventHandler>,
+    any_handler: Option<AnyEventHandler>,
+    fallback: Option<FallbackHandler>,
+    connect_handler: Option<ConnectHandler>,
+    disconnect_handler: Option<DisconnectHandler>,
+    server_side_handlers: HashMap<String, ServerSideHandler>,
+}
+
+impl EventRouter {
+    pub fn new(namespace: impl Into<String>) -> Self {
+        Self {
+            namespace: namespace.into(),
+            handlers: HashMap::new(),
+            any_handler: None,
+            fallback: None,
+            connect_handler: None,
+            disconnect_handler: None,
+            server_side_handlers: HashMap::new(),
+        }
+    }
+
+    pub fn get_namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    pub fn on<T, F, Fut>(&mut self, event_name: impl Into<String>, handler: F) -> &mut Self
+    where
+        T: DeserializeOwned + Send + 'static,
+        F: Fn(Arc<SocketIoSocket>, T, Option<AckSender>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let handler: EventHandler = Box::new(move |socket, event, ack| {
+            let args = event.get_args()?;
+            Ok(Box::pin(handler(socket, args, ack)))
+        });
+
+        self.handlers.insert(event_name.into(), handler);
+        self
+    }
+
+    /// Called for every event before the event handler
+    pub fn on_any<F, Fut>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(Arc<SocketIoSocket>, SocketIoEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.any_handler = Some(Box::new(move |socket, event| {
+            Box::pin(handler(socket, event))
+        }));
+        self
+    }
+
+    /// Called for events without registered handler
+    pub fn fallback<F, Fut>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(Arc<SocketIoSocket>, SocketIoEvent, Option<AckSender>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.fallback = Some(Box::new(move |socket, event, ack| {
+            Box::pin(handler(socket, event, ack))
+        }));
+        self
+    }
+
+    pub fn on_connect<F, Fut>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(Arc<SocketIoSocket>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.connect_handler = Some(Box::new(move |socket| Box::pin(handler(socket))));
+        self
+    }
+
+    /// Called once socket has left the namespace
+    pub fn on_disconnect<F, Fut>(&mut self, handler: F) -> &mut Self
+    where
+        F: Fn(Arc<SocketIoSocket>, DisconnectReason) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.disconnect_handler = Some(Box::new(move |socket, reason| {
+            Box::pin(handler(socket, reason))
+        }));
+        self
+    }
+
+    /// Handles events emitted by other nodes of the cluster. Returned value is the ack response
+    pub fn on_server_side<T, R, F, Fut>(
+        &mut self,
+        event_name: impl Into<String>,
+        handler: F,
+    ) -> &mut Self
+    where
+        T: DeserializeOwned + Send + 'static,
+        R: Serialize,
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = R> + Send + 'static,
+    {
+        let handler: ServerSideHandler = Box::new(move |event| {
+            let future = handler(event.get_args()?);
+            Ok(Box::pin(async move { serialize_args(&future.await) }))
+        });
+
+        self.server_side_handlers.insert(event_name.into(), handler);
+        self
+    }
+
+    pub async fn handle_connect(&self, socket: Arc<SocketIoSocket>) {
+        if let Some(handler) = &self.connect_handler {
+            handler(socket).await;
+        }
+    }
+
+    pub async fn handle_disconnect(&self, socket: Arc<SocketIoSocket>, reason: DisconnectReason) {
+        if let Some(handler) = &self.disconnect_handler {
+            handler(socket, reason).await;
+        }
+    }
+
+    /// Acks are passed to [`SocketIoSocket::handle_ack`]
+    pub async fn dispatch(
+        &self,
+        socket: Arc<SocketIoSocket>,
+        message: SocketIoMessage,
+    ) -> Result<(), EventRouterError> {
+        for namespace in [message.get_namespace(), socket.get_namespace()] {
+            if namespace != self.namespace {
+                return Err(EventRouterError::NamespaceMismatch {
+                    expected: self.namespace.clone(),
+                    actual: namespace.to_string(),
+                });
+            }
+        }
+
+        if let SocketIoMessage::Ack { .. } | SocketIoMessage::BinaryAck { .. } = &message {
+            socket.handle_ack(message);
+            return Ok(());
+        }
+
+        let event = SocketIoEvent::from_message(message).ok_or(EventRouterError::NotAnEvent)?;
+
+        if let Some(any_handler) = &self.any_handler {
+            any_handler(socket.clone(), event.clone()).await;
+        }
+
+        // Ack can only be sent back to the namespace the event came from
+        let ack = event
+            .ack
+            .map(|ack| AckSender::new(socket.clone(), self.namespace.clone(), ack));
+
+        if let Some(handler) = self.handlers.get(&event.event_name) {
+            let future =
+                handler(socket, &event, ack).map_err(|err| EventRouterError::InvalidArguments {
+                    event_name: event.event_name.clone(),
+                    message: err.to_string(),
+                })?;
+
+            future.await;
+            return Ok(());
+        }
+
+        match &self.fallback {
+            Some(fallback) => {
+                fallback(socket, event, ack).await;
+                Ok(())
+            }
+            None => Err(EventRouterError::UnknownEvent(event.event_name)),
+        }
+    }
+
+    /// Handles event emitted by another node. If the event has ack, `Ack` with its id is returned to be sent back
+    pub async fn dispatch_server_side(
+        &self,
+        message: SocketIoMessage,
+    ) -> Result<Option<SocketIoMessage>, EventRouterError> {
+        if message.get_namespace() != self.namespace {
+            return Err(EventRouterError::NamespaceMismatch {
+                expected: self.namespace.clone(),
+                actual: message.get_namespace().to_string(),
+            });
+        }
+
+        let event = SocketIoEvent::from_message(message).ok_or(EventRouterError::NotAnEvent)?;
+
+        let handler = self
+            .server_side_handlers
+            .get(&event.event_name)
+            .ok_or_else(|| EventRouterError::UnknownEvent(event.event_name.clone()))?;
+
+        let future = handler(&event).map_err(|err| EventRouterError::InvalidArguments {
+            event_name: event.event_name.clone(),
+            message: err.to_string(),
+        })?;
+
+        let data = future.await;
+
+        Ok(event.ack.map(|ack| SocketIoMessage::Ack {
+            namespace: self.namespace.clone().into(),
+            event_name: "".into(),
+            data: data.into(),
+            rest_args: Vec::new(),
+            ack,
+        }))
+    }
+}
+
+pub(crate) fn deserialize_args<T: DeserializeOwned>(
+    data: &str,
+    attachments: &[Vec<u8>],
+) -> Result<T, serde_json::Error> {
+    let mut args: Vec<serde_json::Value> = serde_json::from_str(&format!("[{}]", data))?;
+
+    for arg in args.iter_mut() {
+        replace_placeholders(arg, attachments);
+    }
+
+    let value = match args.len() {
+        0 => serde_json::Value::Null,
+        1 => args.pop().unwrap(),
+        _ => serde_json::Value::Array(args),
+    };
+
+    serde_json::from_value(value)
+}
+
+fn replace_placeholders(value: &mut serde_json::Value, attachments: &[Vec<u8>]) {
+    match value {
+        serde_json::Value::Object(fields) => {
+            if fields.get("_placeholder") == Some(&serde_json::Value::Bool(true)) {
+                let attachment = fields
+                    .get("num")
+                    .and_then(|num| num.as_u64())
+                    .and_then(|num| attachments.get(num as usize));
+
+                if let Some(attachment) = attachment {
+                    *value = serde_json::Value::from(attachment.clone());
+                    return;
+                }
+            }
+
+            for item in fields.values_mut() {
+                replace_placeholders(item, attachments);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                replace_placeholders(item, attachments);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::OutgoingQueue;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct ChatMsg {
+        text: String,
+    }
+
+    fn create_socket() -> (Arc<SocketIoSocket>, Arc<OutgoingQueue>) {
+        let queue = Arc::new(OutgoingQueue::new());
+        let socket = SocketIoSocket::new("sid".to_string(), "/chat".to_string(), queue.clone());
+        (Arc::new(socket), queue)
+    }
+
+    fn create_event(event_name: &str, data: &str, ack: Option<i64>) -> SocketIoMessage {
+        SocketIoMessage::Event {
+            namespace: "/chat".into(),
+            event_name: event_name.to_string().into(),
+            data: data.to_string().into(),
+            rest_args: Vec::new(),
+            ack,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_typed_handlers() {
+        let (socket, outgoing) = create_socket();
+        let received = Arc::new(Mutex::new(Vec::new()));
+
+        let mut router = EventRouter::new("/chat");
+
+        let received_copy = received.clone();
+        router.on("chat", move |socket, args: ChatMsg, ack| {
+            let received = received_copy.clone();
+            async move {
+                received.lock().unwrap().push(args.text.clone());
+                socket.emit("echo", &args.text).unwrap();
+                ack.unwrap().send(&args.text).unwrap();
+            }
+        });
+
+        let received_copy = received.clone();
+        router.on("move", move |_, args: (i32, i32), _| {
+            let received = received_copy.clone();
+            async move {
+                received.lock().unwrap().push(format!("{:?}", args));
+            }
+        });
+
+        router
+            .dispatch(
+                socket.clone(),
+                create_event("chat", r#"{"text":"hi"}"#, Some(1)),
+            )
+            .await
+            .unwrap();
+        router
+            .dispatch(socket.clone(), create_event("move", "1,2", None))
+            .await
+            .unwrap();
+
+        assert_eq!(*received.lock().unwrap(), vec!["hi", "(1, 2)"]);
+        assert_eq!(
+            outgoing.pop().await.unwrap().payload.text_frame,
+            r#"42/chat,["echo","hi"]"#
+        );
+        assert_eq!(
+            outgoing.pop().await.unwrap().payload.text_frame,
+            r#"43/chat,1["hi"]"#
+        );
+
+        let result = router
+            .dispatch(socket.clone(), create_event("chat", "1", None))
+            .await;
+        assert!(matches!(
+            result,
+            Err(EventRouterError::InvalidArguments { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_any_and_fallback() {
+        let (socket, _outgoing) = create_socket();
+        let received = Arc::new(Mutex::new(Vec::new()));
+
+        let mut router = EventRouter::new("/chat");
+        router.on("known", |_, _: (), _| async {});
+
+        assert_eq!(
+            router
+                .dispatch(socket.clone(), create_event("unknown", "", None))
+                .await,
+            Err(EventRouterError::UnknownEvent("unknown".to_string()))
+        );
+
+        let received_copy = received.clone();
+        router.on_any(move |_, event| {
+            let received = received_copy.clone();
+            async move {
+                received
+                    .lock()
+                    .unwrap()
+                    .push(format!("any:{}", event.event_name));
+            }
+        });
+
+        let received_copy = received.clone();
+        router.fallback(move |_, event, _| {
+            let received = received_copy.clone();
+            async move {
+                received
+                    .lock()
+                    .unwrap()
+                    .push(format!("fallback:{}", event.event_name));
+            }
+        });
+
+        router
+            .dispatch(socket.clone(), create_event("known", "", None))
+            .await
+            .unwrap();
+        router
+            .dispatch(socket.clone(), create_event("unknown", "", None))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec!["any:known", "any:unknown", "fallback:unknown"]
+        );
+
+        let result = router
+            .dispatch(
+                socket,
+                SocketIoMessage::Disconnect {
+                    namespace: "/".into(),
+                },
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(EventRouterError::NamespaceMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_binary_args() {
+        let data: Vec<u8> =
+            deserialize_args(r#"{"_placeholder":true,"num":0}"#, &[vec![1, 2, 3]]).unwrap();
+        assert_eq!(data, vec![1, 2, 3]);
+    }
+}
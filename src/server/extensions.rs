@@ -0,0 +1,82 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::Arc,
+};
+
+/// Values stored by type, one per type. Clones share the values
+#[derive(Clone, Default)]
+pub struct Extensions {
+    items: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+}
+
+impl Extensions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if a value of the same type is replaced
+    pub fn insert<T: Send + Sync + 'static>(&mut self, value: T) -> bool {
+        self.items
+            .insert(TypeId::of::<T>(), Arc::new(value))
+            .is_some()
+    }
+
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.items.get(&TypeId::of::<T>())?.downcast_ref()
+    }
+
+    pub fn remove<T: Send + Sync + 'static>(&mut self) -> Option<Arc<T>> {
+        let value = self.items.remove(&TypeId::of::<T>())?;
+        Some(value.downcast().unwrap())
+    }
+
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.items.contains_key(&TypeId::of::<T>())
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+impl std::fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Extensions")
+            .field("len", &self.items.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct UserId(u64);
+
+    #[test]
+    fn test_by_type() {
+        let mut extensions = Extensions::new();
+
+        assert!(!extensions.insert(UserId(1)));
+        assert!(!extensions.insert(String::from("tenant")));
+        assert!(extensions.insert(UserId(2)));
+
+        assert_eq!(extensions.get::<UserId>(), Some(&UserId(2)));
+        assert_eq!(extensions.get::<String>().unwrap(), "tenant");
+        assert_eq!(extensions.get::<u64>(), None);
+
+        let cloned = extensions.clone();
+
+        assert_eq!(extensions.remove::<UserId>().unwrap().0, 2);
+        assert!(!extensions.contains::<UserId>());
+        assert_eq!(extensions.len(), 1);
+
+        assert_eq!(cloned.get::<UserId>(), Some(&UserId(2)));
+    }
+}
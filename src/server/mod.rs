@@ -0,0 +1,28 @@
+mod socket;
+pub use socket::*;
+mod socket_descriptor;
+pub use socket_descriptor::*;
+mod extensions;
+pub use extensions::*;
+mod event_router;
+pub use event_router::*;
+mod ack_sender;
+pub use ack_sender::*;
+mod socket_emitter;
+pub use socket_emitter::*;
+mod adapter;
+pub use adapter::*;
+mod namespace;
+pub use namespace::*;
+mod outgoing_queue;
+pub use outgoing_queue::*;
+#[cfg(feature = "polling")]
+mod transport_writer;
+#[cfg(feature = "polling")]
+pub use transport_writer::*;
+mod disconnect_reason;
+pub use disconnect_reason::*;
+mod socket_io_connection;
+pub use socket_io_connection::*;
+mod socket_io_server;
+pub use socket_io_server::*;
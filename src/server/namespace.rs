@@ -0,0 +1,567 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use futures_util::future::join_all;
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::time::Instant;
+
+use crate::{
+    AckError, AckResponse, DisconnectReason, EmitOptions, EventRouter, EventRouterError,
+    LocalAdapter, SocketDescriptor, SocketIoAdapter, SocketIoMessage, SocketIoSocket,
+    SocketsSelector,
+};
+
+use super::{socket::serialize_args, socket_emitter::emit_with_ack_until};
+
+/// Sockets connected to one namespace and their event router
+pub struct SocketIoNamespace {
+    router: EventRouter,
+    adapter: Arc<dyn SocketIoAdapter>,
+    sockets: Mutex<HashMap<String, Arc<SocketIoSocket>>>,
+    next_server_side_ack: AtomicI64,
+}
+
+impl SocketIoNamespace {
+    pub fn new(router: EventRouter) -> Self {
+        Self::new_with_adapter(router, Arc::new(LocalAdapter))
+    }
+
+    pub fn new_with_adapter(router: EventRouter, adapter: Arc<dyn SocketIoAdapter>) -> Self {
+        Self {
+            router,
+            adapter,
+            sockets: Mutex::new(HashMap::new()),
+            next_server_side_ack: AtomicI64::new(0),
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        self.router.get_namespace()
+    }
+
+    pub fn get_router(&self) -> &EventRouter {
+        &self.router
+    }
+
+    pub fn get_adapter(&self) -> &Arc<dyn SocketIoAdapter> {
+        &self.adapter
+    }
+
+    pub fn add_socket(&self, socket: Arc<SocketIoSocket>) {
+        assert_eq!(socket.get_namespace(), self.get_name());
+
+        self.sockets
+            .lock()
+            .unwrap()
+            .insert(socket.get_id().to_string(), socket);
+    }
+
+    pub fn remove_socket(&self, id: &str) -> Option<Arc<SocketIoSocket>> {
+        self.sockets.lock().unwrap().remove(id)
+    }
+
+    pub fn get_socket(&self, id: &str) -> Option<Arc<SocketIoSocket>> {
+        self.sockets.lock().unwrap().get(id).cloned()
+    }
+
+    pub fn sockets_count(&self) -> usize {
+        self.sockets.lock().unwrap().len()
+    }
+
+    pub async fn handle_message(
+        &self,
+        socket: Arc<SocketIoSocket>,
+        message: SocketIoMessage,
+    ) -> Result<(), EventRouterError> {
+        self.router.dispatch(socket, message).await
+    }
+
+    /// Targets all the sockets of namespace
+    pub fn broadcast(&self) -> BroadcastOperator {
+        let sockets = self.sockets.lock().unwrap().values().cloned().collect();
+        BroadcastOperator::new(sockets)
+    }
+
+    /// Targets sockets of all the nodes. Operations are performed by the adapter
+    pub fn select(&self) -> SocketsOperator<'_> {
+        SocketsOperator::new(self)
+    }
+
+    pub fn in_room(&self, room: &str) -> SocketsOperator<'_> {
+        self.select().in_room(room)
+    }
+
+    /// Emits to the other nodes of the cluster. See [`EventRouter::on_server_side`]
+    pub async fn server_side_emit<T: Serialize + ?Sized>(&self, event_name: &str, args: &T) {
+        let message = self.server_side_message(event_name, args, None);
+
+        self.adapter.server_side_emit(self, &message, None).await;
+    }
+
+    /// Collects responses of the other nodes, one result per node. Nodes not answered within `timeout`
+    /// give [`AckError::Timeout`]. Responses are matched by ack id, the ones to other emits are skipped
+    pub async fn server_side_emit_with_ack<T: Serialize + ?Sized, R: DeserializeOwned>(
+        &self,
+        event_name: &str,
+        args: &T,
+        timeout: Duration,
+    ) -> Vec<Result<R, AckError>> {
+        let ack = self.next_server_side_ack.fetch_add(1, Ordering::SeqCst);
+        let message = self.server_side_message(event_name, args, Some(ack));
+        let deadline = Instant::now() + timeout;
+
+        // The adapter is not trusted to honour the deadline
+        let responses = tokio::time::timeout_at(
+            deadline,
+            self.adapter
+                .server_side_emit(self, &message, Some(deadline)),
+        )
+        .await
+        .unwrap_or_else(|_| vec![Err(AckError::Timeout)]);
+
+        let mut result = Vec::with_capacity(responses.len());
+
+        for response in responses {
+            let response = match response {
+                Ok(response) => response,
+                Err(err) => {
+                    result.push(Err(err));
+                    continue;
+                }
+            };
+
+            if response.get_namespace() != self.get_name() {
+                continue;
+            }
+
+            let data = response.get_args_data().unwrap_or_default().into_owned();
+
+            match response {
+                SocketIoMessage::Ack {
+                    event_name,
+                    ack: response_ack,
+                    ..
+                } if response_ack == ack => {
+                    result.push(AckResponse::new(event_name.as_str(), &data, Vec::new()).get_args())
+                }
+                _ => {}
+            }
+        }
+
+        result
+    }
+
+    /// Handles server side emit received by the adapter from another node
+    pub async fn local_server_side_emit(
+        &self,
+        message: SocketIoMessage,
+    ) -> Result<Option<SocketIoMessage>, EventRouterError> {
+        self.router.dispatch_server_side(message).await
+    }
+
+    /// Sends `Disconnect` to the client. With `close` the whole Engine.IO connection is closed
+    pub async fn disconnect_socket(&self, socket: &Arc<SocketIoSocket>, close: bool) {
+        if let Some(connection) = socket.get_connection() {
+            if connection.remove_socket(self.get_name()).is_none() {
+                return;
+            }
+        }
+
+        let _ = socket.send_message(&SocketIoMessage::Disconnect {
+            namespace: self.get_name().to_string().into(),
+        });
+
+        self.detach_socket(socket.clone(), DisconnectReason::ServerNamespaceDisconnect)
+            .await;
+
+        if close {
+            socket.get_outgoing_queue().close();
+        }
+    }
+
+    pub fn local_fetch_sockets(&self, selector: &SocketsSelector) -> Vec<SocketDescriptor> {
+        let mut result: Vec<_> = self
+            .select_local(selector)
+            .iter()
+            .map(|socket| socket.to_descriptor())
+            .collect();
+
+        result.sort_by(|a, b| a.id.cmp(&b.id));
+        result
+    }
+
+    pub fn local_add_sockets(&self, selector: &SocketsSelector, rooms: &[String]) {
+        for socket in self.select_local(selector) {
+            for room in rooms {
+                socket.join(room);
+            }
+        }
+    }
+
+    pub fn local_del_sockets(&self, selector: &SocketsSelector, rooms: &[String]) {
+        for socket in self.select_local(selector) {
+            for room in rooms {
+                socket.leave(room);
+            }
+        }
+    }
+
+    pub async fn local_disconnect_sockets(&self, selector: &SocketsSelector, close: bool) {
+        for socket in self.select_local(selector) {
+            self.disconnect_socket(&socket, close).await;
+        }
+    }
+
+    /// Fails pending acks and calls disconnect handler. Does nothing if socket is already removed
+    pub(crate) async fn detach_socket(
+        &self,
+        socket: Arc<SocketIoSocket>,
+        reason: DisconnectReason,
+    ) {
+        if self.remove_socket(socket.get_id()).is_none() {
+            return;
+        }
+
+        socket.fail_pending_acks();
+        self.router.handle_disconnect(socket, reason).await;
+    }
+
+    fn server_side_message<T: Serialize + ?Sized>(
+        &self,
+        event_name: &str,
+        args: &T,
+        ack: Option<i64>,
+    ) -> SocketIoMessage {
+        SocketIoMessage::Event {
+            namespace: self.get_name().to_string().into(),
+            event_name: event_name.to_string().into(),
+            data: serialize_args(args).into(),
+            rest_args: Vec::new(),
+            ack,
+        }
+    }
+
+    fn select_local(&self, selector: &SocketsSelector) -> Vec<Arc<SocketIoSocket>> {
+        self.sockets
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|socket| selector.matches(socket))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Utility operations on sockets of namespace, including the ones connected to other nodes
+pub struct SocketsOperator<'n> {
+    namespace: &'n SocketIoNamespace,
+    selector: SocketsSelector,
+}
+
+impl<'n> SocketsOperator<'n> {
+    pub fn new(namespace: &'n SocketIoNamespace) -> Self {
+        Self {
+            namespace,
+            selector: SocketsSelector::default(),
+        }
+    }
+
+    /// Several rooms target sockets being in any of them
+    pub fn in_room(mut self, room: &str) -> Self {
+        self.selector.rooms.push(room.to_string());
+        self
+    }
+
+    pub fn except(mut self, room: &str) -> Self {
+        self.selector.except.push(room.to_string());
+        self
+    }
+
+    pub fn get_selector(&self) -> &SocketsSelector {
+        &self.selector
+    }
+
+    pub async fn fetch_sockets(&self) -> Vec<SocketDescriptor> {
+        self.namespace
+            .get_adapter()
+            .fetch_sockets(self.namespace, &self.selector)
+            .await
+    }
+
+    pub async fn sockets_join(&self, rooms: &[&str]) {
+        let rooms: Vec<String> = rooms.iter().map(|room| room.to_string()).collect();
+
+        self.namespace
+            .get_adapter()
+            .add_sockets(self.namespace, &self.selector, &rooms)
+            .await
+    }
+
+    pub async fn sockets_leave(&self, rooms: &[&str]) {
+        let rooms: Vec<String> = rooms.iter().map(|room| room.to_string()).collect();
+
+        self.namespace
+            .get_adapter()
+            .del_sockets(self.namespace, &self.selector, &rooms)
+            .await
+    }
+
+    pub async fn disconnect_sockets(&self, close: bool) {
+        self.namespace
+            .get_adapter()
+            .disconnect_sockets(self.namespace, &self.selector, close)
+            .await
+    }
+}
+
+/// Emits to several sockets at once
+pub struct BroadcastOperator {
+    sockets: Vec<Arc<SocketIoSocket>>,
+    timeout: Option<Duration>,
+    options: EmitOptions,
+}
+
+impl BroadcastOperator {
+    pub fn new(sockets: Vec<Arc<SocketIoSocket>>) -> Self {
+        Self {
+            sockets,
+            timeout: None,
+            options: EmitOptions::default(),
+        }
+    }
+
+    pub fn except(mut self, id: &str) -> Self {
+        self.sockets.retain(|socket| socket.get_id() != id);
+        self
+    }
+
+    /// Limits waiting time of [`Self::emit_with_ack`]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Emit is dropped for sockets with backed up connection
+    pub fn volatile(mut self) -> Self {
+        self.options.volatile = true;
+        self
+    }
+
+    pub fn compress(mut self, compress: bool) -> Self {
+        self.options.compress = compress;
+        self
+    }
+
+    pub fn get_sockets(&self) -> &[Arc<SocketIoSocket>] {
+        &self.sockets
+    }
+
+    /// Sockets with closed connection are skipped
+    pub fn emit<T: Serialize + ?Sized>(self, event_name: &str, args: &T) {
+        let data = serialize_args(args);
+
+        for socket in &self.sockets {
+            let message = SocketIoMessage::Event {
+                namespace: socket.get_namespace().to_string().into(),
+                event_name: event_name.to_string().into(),
+                data: data.clone().into(),
+                rest_args: Vec::new(),
+                ack: None,
+            };
+
+            let _ = socket.send_message_with_options(&message, self.options);
+        }
+    }
+
+    /// Collects answers of all targeted sockets. Every socket has its own result: answer, timeout or closed connection
+    pub async fn emit_with_ack<T: Serialize + ?Sized, R: DeserializeOwned>(
+        self,
+        event_name: &str,
+        args: &T,
+    ) -> Vec<(String, Result<R, AckError>)> {
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        let data = serialize_args(args);
+
+        // Sockets are awaited concurrently, so back-pressure of one connection does not delay the others
+        let requests = self.sockets.iter().map(|socket| {
+            let data = data.clone();
+
+            async move {
+                let response =
+                    emit_with_ack_until(socket, event_name, data, self.options, deadline).await;
+                (socket.get_id().to_string(), response)
+            }
+        });
+
+        join_all(requests).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OutgoingQueue, OutgoingQueueSettings, QueueFullPolicy};
+
+    #[tokio::test]
+    async fn test_broadcast_with_ack() {
+        let namespace = Arc::new(SocketIoNamespace::new(EventRouter::new("/")));
+        let mut queues = Vec::new();
+
+        for id in ["a", "b", "c"] {
+            let queue = Arc::new(OutgoingQueue::new());
+            namespace.add_socket(Arc::new(SocketIoSocket::new(
+                id.to_string(),
+                "/".to_string(),
+                queue.clone(),
+            )));
+            queues.push((id, queue));
+        }
+
+        let clients_namespace = namespace.clone();
+        let clients = tokio::spawn(async move {
+            // "c" is excluded
+            for (id, queue) in queues.iter().take(2) {
+                let outgoing = queue.pop().await.unwrap();
+                assert_eq!(outgoing.payload.text_frame, r#"420["ping",1]"#);
+
+                // "b" does not answer
+                if *id != "b" {
+                    let socket = clients_namespace.get_socket(id).unwrap();
+                    let ack = SocketIoMessage::deserialize(&format!(r#"30["{}"]"#, id)).unwrap();
+                    clients_namespace.handle_message(socket, ack).await.unwrap();
+                }
+            }
+        });
+
+        let mut result: Vec<(String, Result<String, AckError>)> = namespace
+            .broadcast()
+            .except("c")
+            .timeout(Duration::from_millis(100))
+            .emit_with_ack("ping", &1)
+            .await;
+
+        result.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(
+            result,
+            vec![
+                ("a".to_string(), Ok("a".to_string())),
+                ("b".to_string(), Err(AckError::Timeout)),
+            ]
+        );
+
+        clients.await.unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_broadcast_with_ack_does_not_wait_for_full_queue() {
+        let namespace = Arc::new(SocketIoNamespace::new(EventRouter::new("/")));
+
+        let full_queue = Arc::new(OutgoingQueue::new_with_settings(OutgoingQueueSettings {
+            high_water_mark: 1,
+            policy: QueueFullPolicy::Block,
+        }));
+        let full_socket = Arc::new(SocketIoSocket::new(
+            "a".to_string(),
+            "/".to_string(),
+            full_queue,
+        ));
+        full_socket.emit("backlog", &1).unwrap();
+        namespace.add_socket(full_socket);
+
+        let queue = Arc::new(OutgoingQueue::new());
+        namespace.add_socket(Arc::new(SocketIoSocket::new(
+            "b".to_string(),
+            "/".to_string(),
+            queue.clone(),
+        )));
+
+        let client_namespace = namespace.clone();
+        let client = tokio::spawn(async move {
+            queue.pop().await.unwrap();
+            let socket = client_namespace.get_socket("b").unwrap();
+            let ack = SocketIoMessage::deserialize(r#"30["b"]"#).unwrap();
+            client_namespace.handle_message(socket, ack).await.unwrap();
+        });
+
+        let mut result: Vec<(String, Result<String, AckError>)> = namespace
+            .broadcast()
+            .timeout(Duration::from_secs(1))
+            .emit_with_ack("ping", &1)
+            .await;
+
+        result.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(
+            result,
+            vec![
+                ("a".to_string(), Err(AckError::Timeout)),
+                ("b".to_string(), Ok("b".to_string())),
+            ]
+        );
+
+        client.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sockets_operations() {
+        let namespace = SocketIoNamespace::new(EventRouter::new("/"));
+        let mut queues = HashMap::new();
+
+        for id in ["a", "b", "c"] {
+            let queue = Arc::new(OutgoingQueue::new());
+            namespace.add_socket(Arc::new(SocketIoSocket::new(
+                id.to_string(),
+                "/".to_string(),
+                queue.clone(),
+            )));
+            queues.insert(id, queue);
+        }
+
+        namespace.get_socket("a").unwrap().join("user:1");
+        namespace.get_socket("b").unwrap().join("user:1");
+
+        namespace
+            .in_room("user:1")
+            .except("b")
+            .sockets_join(&["admins"])
+            .await;
+
+        let sockets = namespace.in_room("admins").fetch_sockets().await;
+        assert_eq!(sockets.len(), 1);
+        assert_eq!(sockets[0].id, "a");
+        assert_eq!(sockets[0].rooms, vec!["a", "admins", "user:1"]);
+
+        let json = serde_json::to_string(&sockets[0]).unwrap();
+        let deserialized: SocketDescriptor = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.rooms, sockets[0].rooms);
+        assert_eq!(deserialized.handshake, sockets[0].handshake);
+
+        namespace.select().sockets_leave(&["user:1"]).await;
+        assert!(namespace.in_room("user:1").fetch_sockets().await.is_empty());
+
+        namespace.in_room("a").disconnect_sockets(false).await;
+        namespace.in_room("b").disconnect_sockets(true).await;
+
+        let ids: Vec<_> = namespace
+            .select()
+            .fetch_sockets()
+            .await
+            .into_iter()
+            .map(|socket| socket.id)
+            .collect();
+        assert_eq!(ids, vec!["c"]);
+
+        assert_eq!(queues["a"].try_pop().unwrap().payload.text_frame, "41");
+        assert!(!queues["a"].is_closed());
+        assert_eq!(queues["b"].try_pop().unwrap().payload.text_frame, "41");
+        assert!(queues["b"].is_closed());
+        assert!(queues["c"].is_empty());
+    }
+}
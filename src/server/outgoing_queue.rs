@@ -0,0 +1,421 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use tokio::sync::Notify;
+
+use crate::{OutgoingPayload, SocketSendError};
+
+/// What to do with a new payload when queue depth reached high-water mark
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueFullPolicy {
+    /// Payload is rejected with [`SocketSendError::QueueFull`]; async emits wait for space, see [`OutgoingQueue::push_async`]
+    Block,
+    /// Queued volatile payloads are dropped to free space; if there are none - payload is rejected as in `Block`
+    DropVolatile,
+    DropOldest,
+    /// Queue is closed, slow client is disconnected
+    Disconnect,
+}
+
+#[derive(Debug, Clone)]
+pub struct OutgoingQueueSettings {
+    pub high_water_mark: usize,
+    pub policy: QueueFullPolicy,
+}
+
+impl Default for OutgoingQueueSettings {
+    fn default() -> Self {
+        Self {
+            high_water_mark: 1024,
+            policy: QueueFullPolicy::Block,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutgoingQueueMetrics {
+    pub depth: usize,
+    pub max_depth: usize,
+    pub enqueued: u64,
+    /// Volatile payloads and payloads evicted by the policy
+    pub dropped: u64,
+    pub rejected: u64,
+}
+
+struct QueueState {
+    queue: VecDeque<OutgoingPayload>,
+    metrics: OutgoingQueueMetrics,
+}
+
+/// Payloads waiting to be written to the transport. Shared by the sockets of one connection
+pub struct OutgoingQueue {
+    settings: OutgoingQueueSettings,
+    state: Mutex<QueueState>,
+    notify: Notify,
+    space_notify: Notify,
+    closed: AtomicBool,
+    slow_client: AtomicBool,
+}
+
+impl OutgoingQueue {
+    pub fn new() -> Self {
+        Self::new_with_settings(OutgoingQueueSettings::default())
+    }
+
+    pub fn new_with_settings(settings: OutgoingQueueSettings) -> Self {
+        Self {
+            settings,
+            state: Mutex::new(QueueState {
+                queue: VecDeque::new(),
+                metrics: OutgoingQueueMetrics::default(),
+            }),
+            notify: Notify::new(),
+            space_notify: Notify::new(),
+            closed: AtomicBool::new(false),
+            slow_client: AtomicBool::new(false),
+        }
+    }
+
+    pub fn get_settings(&self) -> &OutgoingQueueSettings {
+        &self.settings
+    }
+
+    /// Volatile payloads are dropped if there are payloads the transport has not taken yet
+    pub fn push(&self, outgoing: OutgoingPayload) -> Result<(), SocketSendError> {
+        match self.try_push(outgoing) {
+            Ok(()) => Ok(()),
+            Err((SocketSendError::QueueFull, _)) => {
+                self.state.lock().unwrap().metrics.rejected += 1;
+                Err(SocketSendError::QueueFull)
+            }
+            Err((err, _)) => Err(err),
+        }
+    }
+
+    /// Same as [`Self::push`], but waits for space instead of [`SocketSendError::QueueFull`]
+    pub async fn push_async(&self, mut outgoing: OutgoingPayload) -> Result<(), SocketSendError> {
+        loop {
+            let notified = self.space_notify.notified();
+
+            match self.try_push(outgoing) {
+                Ok(()) => return Ok(()),
+                Err((SocketSendError::QueueFull, returned)) => outgoing = returned,
+                Err((err, _)) => return Err(err),
+            }
+
+            notified.await;
+        }
+    }
+
+    /// Engine.IO control packets (ping, pong, noop, close) are queued regardless of high-water mark and policy
+    pub fn push_control(&self, outgoing: OutgoingPayload) -> Result<(), SocketSendError> {
+        if self.is_closed() {
+            return Err(SocketSendError::ConnectionClosed);
+        }
+
+        {
+            let mut state = self.state.lock().unwrap();
+            state.queue.push_back(outgoing);
+            state.metrics.enqueued += 1;
+            state.metrics.max_depth = state.metrics.max_depth.max(state.queue.len());
+        }
+
+        self.notify.notify_one();
+        Ok(())
+    }
+
+    /// Rejected payload is given back
+    fn try_push(
+        &self,
+        outgoing: OutgoingPayload,
+    ) -> Result<(), (SocketSendError, OutgoingPayload)> {
+        if self.is_closed() {
+            return Err((SocketSendError::ConnectionClosed, outgoing));
+        }
+
+        {
+            let mut state = self.state.lock().unwrap();
+
+            if outgoing.options.volatile && !state.queue.is_empty() {
+                state.metrics.dropped += 1;
+                return Ok(());
+            }
+
+            if state.queue.len() >= self.settings.high_water_mark {
+                match self.settings.policy {
+                    QueueFullPolicy::Block => {
+                        return Err((SocketSendError::QueueFull, outgoing));
+                    }
+                    QueueFullPolicy::DropVolatile => {
+                        let depth = state.queue.len();
+                        state.queue.retain(|queued| !queued.options.volatile);
+                        state.metrics.dropped += (depth - state.queue.len()) as u64;
+
+                        if state.queue.len() >= self.settings.high_water_mark {
+                            return Err((SocketSendError::QueueFull, outgoing));
+                        }
+                    }
+                    QueueFullPolicy::DropOldest => {
+                        while state.queue.len() >= self.settings.high_water_mark {
+                            state.queue.pop_front();
+                            state.metrics.dropped += 1;
+                        }
+                    }
+                    QueueFullPolicy::Disconnect => {
+                        state.metrics.rejected += 1;
+                        drop(state);
+
+                        self.slow_client.store(true, Ordering::SeqCst);
+                        self.close();
+                        return Err((SocketSendError::ConnectionClosed, outgoing));
+                    }
+                }
+            }
+
+            state.queue.push_back(outgoing);
+            state.metrics.enqueued += 1;
+            state.metrics.max_depth = state.metrics.max_depth.max(state.queue.len());
+        }
+
+        self.notify.notify_one();
+        Ok(())
+    }
+
+    pub fn try_pop(&self) -> Option<OutgoingPayload> {
+        let result = self.state.lock().unwrap().queue.pop_front();
+
+        if result.is_some() {
+            self.space_notify.notify_waiters();
+        }
+
+        result
+    }
+
+    /// Waits for the next payload. `None` means queue is closed and drained
+    pub async fn pop(&self) -> Option<OutgoingPayload> {
+        loop {
+            let notified = self.notify.notified();
+
+            if let Some(outgoing) = self.try_pop() {
+                return Some(outgoing);
+            }
+
+            if self.is_closed() {
+                return None;
+            }
+
+            notified.await;
+        }
+    }
+
+    /// Waits until a payload can be pushed without [`SocketSendError::QueueFull`]
+    pub async fn writable(&self) {
+        if !matches!(
+            self.settings.policy,
+            QueueFullPolicy::Block | QueueFullPolicy::DropVolatile
+        ) {
+            return;
+        }
+
+        loop {
+            let notified = self.space_notify.notified();
+
+            if self.is_closed() || self.len() < self.settings.high_water_mark {
+                return;
+            }
+
+            notified.await;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get_metrics(&self) -> OutgoingQueueMetrics {
+        let state = self.state.lock().unwrap();
+
+        let mut result = state.metrics.clone();
+        result.depth = state.queue.len();
+        result
+    }
+
+    /// Already queued payloads can still be taken
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        self.notify.notify_waiters();
+        self.space_notify.notify_waiters();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Queue was closed by [`QueueFullPolicy::Disconnect`]
+    pub fn is_slow_client(&self) -> bool {
+        self.slow_client.load(Ordering::SeqCst)
+    }
+}
+
+impl Default for OutgoingQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{EmitOptions, SocketIoPayload};
+
+    fn create_payload(text: &str, volatile: bool) -> OutgoingPayload {
+        let mut payload = SocketIoPayload::new();
+        payload.text_frame.push_str(text);
+
+        OutgoingPayload::new(
+            payload,
+            EmitOptions {
+                volatile,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn create_queue(policy: QueueFullPolicy) -> OutgoingQueue {
+        OutgoingQueue::new_with_settings(OutgoingQueueSettings {
+            high_water_mark: 2,
+            policy,
+        })
+    }
+
+    fn pop_all(queue: &OutgoingQueue) -> Vec<String> {
+        let mut result = Vec::new();
+
+        while let Some(outgoing) = queue.try_pop() {
+            result.push(outgoing.payload.text_frame);
+        }
+
+        result
+    }
+
+    #[tokio::test]
+    async fn test_volatile_is_dropped_when_backed_up() {
+        let queue = OutgoingQueue::new();
+
+        queue.push(create_payload("1", true)).unwrap();
+        queue.push(create_payload("2", false)).unwrap();
+        queue.push(create_payload("3", true)).unwrap();
+
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.pop().await.unwrap().payload.text_frame, "1");
+        assert_eq!(queue.pop().await.unwrap().payload.text_frame, "2");
+
+        queue.close();
+        assert!(queue.pop().await.is_none());
+        assert_eq!(
+            queue.push(create_payload("4", false)).err(),
+            Some(SocketSendError::ConnectionClosed)
+        );
+    }
+
+    #[test]
+    fn test_policies() {
+        let queue = create_queue(QueueFullPolicy::DropOldest);
+        for text in ["1", "2", "3"] {
+            queue.push(create_payload(text, false)).unwrap();
+        }
+        assert_eq!(pop_all(&queue), vec!["2", "3"]);
+
+        let queue = create_queue(QueueFullPolicy::DropVolatile);
+        queue.push(create_payload("1", true)).unwrap();
+        queue.push(create_payload("2", false)).unwrap();
+        queue.push(create_payload("3", false)).unwrap();
+        assert_eq!(
+            queue.push(create_payload("4", false)).err(),
+            Some(SocketSendError::QueueFull)
+        );
+        assert_eq!(pop_all(&queue), vec!["2", "3"]);
+
+        let queue = create_queue(QueueFullPolicy::Disconnect);
+        queue.push(create_payload("1", false)).unwrap();
+        queue.push(create_payload("2", false)).unwrap();
+        assert_eq!(
+            queue.push(create_payload("3", false)).err(),
+            Some(SocketSendError::ConnectionClosed)
+        );
+        assert!(queue.is_closed());
+        assert!(queue.is_slow_client());
+
+        assert_eq!(
+            queue.get_metrics(),
+            OutgoingQueueMetrics {
+                depth: 2,
+                max_depth: 2,
+                enqueued: 2,
+                dropped: 0,
+                rejected: 1,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_block_waits_for_space() {
+        let queue = Arc::new(create_queue(QueueFullPolicy::Block));
+        queue.push(create_payload("1", false)).unwrap();
+        queue.push(create_payload("2", false)).unwrap();
+
+        assert_eq!(
+            queue.push(create_payload("3", false)).err(),
+            Some(SocketSendError::QueueFull)
+        );
+
+        let writer_queue = queue.clone();
+        let writer = tokio::spawn(async move {
+            writer_queue.pop().await.unwrap();
+        });
+
+        queue.writable().await;
+        queue.push(create_payload("3", false)).unwrap();
+        writer.await.unwrap();
+
+        assert_eq!(pop_all(&queue), vec!["2", "3"]);
+        assert_eq!(queue.get_metrics().rejected, 1);
+    }
+
+    #[tokio::test]
+    async fn test_push_async_waits_for_space() {
+        let queue = Arc::new(create_queue(QueueFullPolicy::Block));
+        queue.push(create_payload("1", false)).unwrap();
+        queue.push(create_payload("2", false)).unwrap();
+
+        let pusher_queue = queue.clone();
+        let pusher =
+            tokio::spawn(async move { pusher_queue.push_async(create_payload("3", false)).await });
+
+        tokio::task::yield_now().await;
+        assert!(!pusher.is_finished());
+
+        assert_eq!(queue.pop().await.unwrap().payload.text_frame, "1");
+        pusher.await.unwrap().unwrap();
+
+        assert_eq!(pop_all(&queue), vec!["2", "3"]);
+        assert_eq!(queue.get_metrics().rejected, 0);
+
+        queue.close();
+        assert_eq!(
+            queue.push_async(create_payload("4", false)).await.err(),
+            Some(SocketSendError::ConnectionClosed)
+        );
+    }
+}
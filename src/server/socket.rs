@@ -0,0 +1,308 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc, Mutex, MutexGuard, Weak,
+    },
+    time::Duration,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::sync::oneshot;
+
+use crate::{
+    AckError, AckResponse, DefaultParser, EmitOptions, Extensions, OutgoingPayload, OutgoingQueue,
+    SocketDescriptor, SocketEmitter, SocketHandshake, SocketIoConnection, SocketIoEncodeError,
+    SocketIoEventParameter, SocketIoMessage, SocketIoParser, SocketIoPayload,
+};
+
+pub type SocketIoMessageEncoder =
+    Arc<dyn Fn(&SocketIoMessage) -> Result<SocketIoPayload, SocketIoEncodeError> + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SocketSendError {
+    ConnectionClosed,
+    /// Outgoing queue reached high-water mark. See [`crate::QueueFullPolicy`]
+    QueueFull,
+    /// Message can not be encoded by the parser, e.g. event data is not valid JSON
+    InvalidData(SocketIoEncodeError),
+}
+
+/// Server side Socket.IO socket: a client connected to one namespace
+pub struct SocketIoSocket {
+    id: String,
+    namespace: String,
+    outgoing: Arc<OutgoingQueue>,
+    encoder: SocketIoMessageEncoder,
+    next_ack_id: AtomicI64,
+    pending_acks: Mutex<HashMap<i64, oneshot::Sender<AckResponse>>>,
+    rooms: Mutex<BTreeSet<String>>,
+    handshake: SocketHandshake,
+    extensions: Mutex<Extensions>,
+    connection: Weak<SocketIoConnection>,
+}
+
+impl SocketIoSocket {
+    /// Payloads put to `outgoing` are ready to be written to transport as is
+    pub fn new(id: String, namespace: String, outgoing: Arc<OutgoingQueue>) -> Self {
+        Self::new_with_parser(id, namespace, outgoing, DefaultParser)
+    }
+
+    pub fn new_with_parser<P: SocketIoParser + Send + Sync + 'static>(
+        id: String,
+        namespace: String,
+        outgoing: Arc<OutgoingQueue>,
+        parser: P,
+    ) -> Self {
+        let encoder: SocketIoMessageEncoder =
+            Arc::new(move |message| parser.encode_to_engine_payload(message));
+
+        Self::new_with_encoder(id, namespace, outgoing, encoder)
+    }
+
+    pub(crate) fn new_with_encoder(
+        id: String,
+        namespace: String,
+        outgoing: Arc<OutgoingQueue>,
+        encoder: SocketIoMessageEncoder,
+    ) -> Self {
+        // Like in Socket.IO every socket is in the room named by its id
+        let rooms = Mutex::new(BTreeSet::from([id.clone()]));
+        let handshake = SocketHandshake::new(id.clone());
+
+        Self {
+            id,
+            namespace,
+            outgoing,
+            encoder,
+            next_ack_id: AtomicI64::new(0),
+            pending_acks: Mutex::new(HashMap::new()),
+            rooms,
+            handshake,
+            extensions: Mutex::new(Extensions::new()),
+            connection: Weak::new(),
+        }
+    }
+
+    pub(crate) fn with_connection(mut self, connection: &Arc<SocketIoConnection>) -> Self {
+        self.connection = Arc::downgrade(connection);
+        self
+    }
+
+    pub(crate) fn with_handshake(mut self, handshake: SocketHandshake) -> Self {
+        self.handshake = handshake;
+        self
+    }
+
+    pub fn get_id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn get_namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    pub fn get_outgoing_queue(&self) -> &Arc<OutgoingQueue> {
+        &self.outgoing
+    }
+
+    pub fn get_handshake(&self) -> &SocketHandshake {
+        &self.handshake
+    }
+
+    /// Values attached to the socket, e.g. user id resolved by connect handler.
+    /// Guard must not be held across `.await`
+    pub fn get_extensions(&self) -> MutexGuard<'_, Extensions> {
+        self.extensions.lock().unwrap()
+    }
+
+    /// Returns true if a value of the same type is replaced
+    pub fn insert_extension<T: Send + Sync + 'static>(&self, value: T) -> bool {
+        self.get_extensions().insert(value)
+    }
+
+    pub fn get_extension<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
+        self.get_extensions().get::<T>().cloned()
+    }
+
+    pub fn remove_extension<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.get_extensions().remove::<T>()
+    }
+
+    pub fn join(&self, room: &str) {
+        self.rooms.lock().unwrap().insert(room.to_string());
+    }
+
+    pub fn leave(&self, room: &str) {
+        self.rooms.lock().unwrap().remove(room);
+    }
+
+    pub fn get_rooms(&self) -> Vec<String> {
+        self.rooms.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn is_in_room(&self, room: &str) -> bool {
+        self.rooms.lock().unwrap().contains(room)
+    }
+
+    pub fn to_descriptor(&self) -> SocketDescriptor {
+        SocketDescriptor {
+            id: self.id.clone(),
+            namespace: self.namespace.clone(),
+            rooms: self.get_rooms(),
+            handshake: self.handshake.clone(),
+            extensions: self.get_extensions().clone(),
+        }
+    }
+
+    /// Connection the socket was created for by [`crate::SocketIoServer`]
+    pub(crate) fn get_connection(&self) -> Option<Arc<SocketIoConnection>> {
+        self.connection.upgrade()
+    }
+
+    /// Waits until outgoing queue has space for the next emit
+    pub async fn writable(&self) {
+        self.outgoing.writable().await
+    }
+
+    pub fn send_message(&self, message: &SocketIoMessage) -> Result<(), SocketSendError> {
+        self.send_message_with_options(message, EmitOptions::default())
+    }
+
+    pub fn send_message_with_options(
+        &self,
+        message: &SocketIoMessage,
+        options: EmitOptions,
+    ) -> Result<(), SocketSendError> {
+        let payload = (self.encoder)(message).map_err(SocketSendError::InvalidData)?;
+        self.outgoing.push(OutgoingPayload::new(payload, options))
+    }
+
+    /// Waits for space in outgoing queue instead of [`SocketSendError::QueueFull`]
+    pub async fn send_message_async(
+        &self,
+        message: &SocketIoMessage,
+        options: EmitOptions,
+    ) -> Result<(), SocketSendError> {
+        let payload = (self.encoder)(message).map_err(SocketSendError::InvalidData)?;
+        self.outgoing
+            .push_async(OutgoingPayload::new(payload, options))
+            .await
+    }
+
+    /// Args are serialized as a single event argument
+    pub fn emit<T: Serialize + ?Sized>(
+        &self,
+        event_name: &str,
+        args: &T,
+    ) -> Result<(), SocketSendError> {
+        SocketEmitter::new(self).emit(event_name, args)
+    }
+
+    /// Waits for space in outgoing queue if it is full. See [`crate::QueueFullPolicy::Block`]
+    pub async fn emit_async<T: Serialize + ?Sized>(
+        &self,
+        event_name: &str,
+        args: &T,
+    ) -> Result<(), SocketSendError> {
+        SocketEmitter::new(self).emit_async(event_name, args).await
+    }
+
+    /// Several arguments or binary data
+    pub fn emit_params(
+        &self,
+        event_name: &str,
+        params: Vec<SocketIoEventParameter>,
+    ) -> Result<(), SocketSendError> {
+        SocketEmitter::new(self).emit_params(event_name, params)
+    }
+
+    /// Waits for the client answer without time limit. See [`Self::timeout`]
+    pub async fn emit_with_ack<T: Serialize + ?Sized, R: DeserializeOwned>(
+        &self,
+        event_name: &str,
+        args: &T,
+    ) -> Result<R, AckError> {
+        SocketEmitter::new(self)
+            .emit_with_ack(event_name, args)
+            .await
+    }
+
+    pub fn timeout(&self, timeout: Duration) -> SocketEmitter<'_> {
+        SocketEmitter::new(self).timeout(timeout)
+    }
+
+    /// Emit is dropped if the connection is backed up
+    pub fn volatile(&self) -> SocketEmitter<'_> {
+        SocketEmitter::new(self).volatile()
+    }
+
+    pub fn compress(&self, compress: bool) -> SocketEmitter<'_> {
+        SocketEmitter::new(self).compress(compress)
+    }
+
+    pub fn pending_acks_count(&self) -> usize {
+        self.pending_acks.lock().unwrap().len()
+    }
+
+    /// Resolves pending [`Self::emit_with_ack`]. Returns false if message is not an ack of this socket
+    pub fn handle_ack(&self, message: SocketIoMessage) -> bool {
+        if message.get_namespace() != self.namespace {
+            return false;
+        }
+
+        let data = match message.get_args_data() {
+            Some(data) => data.into_owned(),
+            None => return false,
+        };
+
+        let (ack, response) = match message {
+            SocketIoMessage::Ack {
+                event_name, ack, ..
+            } => (
+                ack,
+                AckResponse::new(event_name.as_str(), &data, Vec::new()),
+            ),
+            SocketIoMessage::BinaryAck {
+                event_name,
+                ack,
+                attachments,
+                ..
+            } => (
+                ack,
+                AckResponse::new(event_name.as_str(), &data, attachments),
+            ),
+            _ => return false,
+        };
+
+        let sender = self.pending_acks.lock().unwrap().remove(&ack);
+
+        match sender {
+            Some(sender) => {
+                let _ = sender.send(response);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn register_ack(&self) -> (i64, oneshot::Receiver<AckResponse>) {
+        let ack = self.next_ack_id.fetch_add(1, Ordering::SeqCst);
+        let (sender, receiver) = oneshot::channel();
+        self.pending_acks.lock().unwrap().insert(ack, sender);
+        (ack, receiver)
+    }
+
+    pub(crate) fn remove_ack(&self, ack: i64) {
+        self.pending_acks.lock().unwrap().remove(&ack);
+    }
+
+    /// Waiting emits get [`AckError::ConnectionClosed`]
+    pub(crate) fn fail_pending_acks(&self) {
+        self.pending_acks.lock().unwrap().clear();
+    }
+}
+
+pub(crate) fn serialize_args<T: Serialize + ?Sized>(args: &T) -> String {
+    serde_json::to_string(args).unwrap()
+}
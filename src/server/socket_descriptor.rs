@@ -0,0 +1,52 @@
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Extensions;
+
+/// What the server knows about the socket from its CONNECT
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SocketHandshake {
+    /// Engine.IO sid of the connection
+    pub sid: String,
+    /// Unix time of the CONNECT in milliseconds
+    pub issued: u64,
+    /// Query of the request which opened the Engine.IO connection
+    pub query: HashMap<String, String>,
+    /// Headers of the request which opened the Engine.IO connection. Names are lowercase
+    pub headers: HashMap<String, String>,
+    /// Object the client passed with CONNECT, empty if there was none
+    pub auth: serde_json::Value,
+}
+
+impl SocketHandshake {
+    pub fn new(sid: String) -> Self {
+        let issued = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+
+        Self {
+            sid,
+            issued,
+            query: HashMap::new(),
+            headers: HashMap::new(),
+            auth: serde_json::Value::Object(Default::default()),
+        }
+    }
+}
+
+/// Snapshot of a socket returned by fetch sockets queries. May describe a socket of another node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SocketDescriptor {
+    pub id: String,
+    pub namespace: String,
+    pub rooms: Vec<String>,
+    pub handshake: SocketHandshake,
+    /// Shares values with the socket. Not passed between nodes, so it is empty for remote sockets
+    #[serde(skip)]
+    pub extensions: Extensions,
+}
@@ -0,0 +1,291 @@
+use std::time::Duration;
+
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::{sync::oneshot, time::Instant};
+
+use crate::{
+    EmitOptions, SocketIoEventParameter, SocketIoMessage, SocketIoSocket, SocketSendError,
+};
+
+use super::{event_router::deserialize_args, socket::serialize_args};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AckError {
+    Timeout,
+    ConnectionClosed,
+    QueueFull,
+    InvalidArguments(String),
+}
+
+impl From<SocketSendError> for AckError {
+    fn from(value: SocketSendError) -> Self {
+        match value {
+            SocketSendError::ConnectionClosed => Self::ConnectionClosed,
+            SocketSendError::QueueFull => Self::QueueFull,
+            SocketSendError::InvalidData(err) => Self::InvalidArguments(format!("{:?}", err)),
+        }
+    }
+}
+
+/// Ack arguments received from the client
+#[derive(Debug, Clone)]
+pub struct AckResponse {
+    pub data: String,
+    pub attachments: Vec<Vec<u8>>,
+}
+
+impl AckResponse {
+    /// Event name of ack message is its first string argument
+    pub fn new(event_name: &str, data: &str, attachments: Vec<Vec<u8>>) -> Self {
+        let data = match (event_name.is_empty(), data.is_empty()) {
+            (true, _) => data.to_string(),
+            (false, true) => serde_json::to_string(event_name).unwrap(),
+            (false, false) => format!("{},{}", serde_json::to_string(event_name).unwrap(), data),
+        };
+
+        Self { data, attachments }
+    }
+
+    /// Same rules as [`crate::SocketIoEvent::get_args`]
+    pub fn get_args<T: DeserializeOwned>(&self) -> Result<T, AckError> {
+        deserialize_args(&self.data, &self.attachments)
+            .map_err(|err| AckError::InvalidArguments(err.to_string()))
+    }
+}
+
+/// Emit options of a single socket
+pub struct SocketEmitter<'s> {
+    socket: &'s SocketIoSocket,
+    timeout: Option<Duration>,
+    options: EmitOptions,
+}
+
+impl<'s> SocketEmitter<'s> {
+    pub fn new(socket: &'s SocketIoSocket) -> Self {
+        Self {
+            socket,
+            timeout: None,
+            options: EmitOptions::default(),
+        }
+    }
+
+    /// Limits waiting time of [`Self::emit_with_ack`]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn volatile(mut self) -> Self {
+        self.options.volatile = true;
+        self
+    }
+
+    pub fn compress(mut self, compress: bool) -> Self {
+        self.options.compress = compress;
+        self
+    }
+
+    pub fn emit<T: Serialize + ?Sized>(
+        self,
+        event_name: &str,
+        args: &T,
+    ) -> Result<(), SocketSendError> {
+        let message = create_event(self.socket, event_name, serialize_args(args), None);
+        self.socket
+            .send_message_with_options(&message, self.options)
+    }
+
+    pub async fn emit_async<T: Serialize + ?Sized>(
+        self,
+        event_name: &str,
+        args: &T,
+    ) -> Result<(), SocketSendError> {
+        let message = create_event(self.socket, event_name, serialize_args(args), None);
+        self.socket.send_message_async(&message, self.options).await
+    }
+
+    pub fn emit_params(
+        self,
+        event_name: &str,
+        params: Vec<SocketIoEventParameter>,
+    ) -> Result<(), SocketSendError> {
+        let message = SocketIoMessage::event_with_params(
+            self.socket.get_namespace().to_string(),
+            event_name.to_string(),
+            params,
+            None,
+        );
+
+        self.socket
+            .send_message_with_options(&message, self.options)
+    }
+
+    pub async fn emit_with_ack<T: Serialize + ?Sized, R: DeserializeOwned>(
+        self,
+        event_name: &str,
+        args: &T,
+    ) -> Result<R, AckError> {
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+
+        emit_with_ack_until(
+            self.socket,
+            event_name,
+            serialize_args(args),
+            self.options,
+            deadline,
+        )
+        .await
+    }
+}
+
+fn create_event(
+    socket: &SocketIoSocket,
+    event_name: &str,
+    data: String,
+    ack: Option<i64>,
+) -> SocketIoMessage {
+    SocketIoMessage::Event {
+        namespace: socket.get_namespace().to_string().into(),
+        event_name: event_name.to_string().into(),
+        data: data.into(),
+        rest_args: Vec::new(),
+        ack,
+    }
+}
+
+/// Deadline covers both waiting for queue space and waiting for the answer
+pub(crate) async fn emit_with_ack_until<R: DeserializeOwned>(
+    socket: &SocketIoSocket,
+    event_name: &str,
+    data: String,
+    options: EmitOptions,
+    deadline: Option<Instant>,
+) -> Result<R, AckError> {
+    let (ack, receiver) = socket.register_ack();
+    let message = create_event(socket, event_name, data, Some(ack));
+
+    let sent = match deadline {
+        Some(deadline) => {
+            match tokio::time::timeout_at(deadline, socket.send_message_async(&message, options))
+                .await
+            {
+                Ok(sent) => sent.map_err(AckError::from),
+                Err(_) => Err(AckError::Timeout),
+            }
+        }
+        None => socket
+            .send_message_async(&message, options)
+            .await
+            .map_err(AckError::from),
+    };
+
+    if let Err(err) = sent {
+        socket.remove_ack(ack);
+        return Err(err);
+    }
+
+    wait_ack(socket, ack, receiver, deadline).await?.get_args()
+}
+
+async fn wait_ack(
+    socket: &SocketIoSocket,
+    ack: i64,
+    receiver: oneshot::Receiver<AckResponse>,
+    deadline: Option<Instant>,
+) -> Result<AckResponse, AckError> {
+    let result = match deadline {
+        Some(deadline) => match tokio::time::timeout_at(deadline, receiver).await {
+            Ok(result) => result,
+            Err(_) => {
+                socket.remove_ack(ack);
+                return Err(AckError::Timeout);
+            }
+        },
+        None => receiver.await,
+    };
+
+    result.map_err(|_| AckError::ConnectionClosed)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{OutgoingQueue, OutgoingQueueSettings, QueueFullPolicy};
+
+    #[tokio::test]
+    async fn test_emit_with_ack() {
+        let outgoing = Arc::new(OutgoingQueue::new());
+        let socket = Arc::new(SocketIoSocket::new(
+            "sid".to_string(),
+            "/".to_string(),
+            outgoing.clone(),
+        ));
+
+        let client_socket = socket.clone();
+        let client_outgoing = outgoing.clone();
+        let client = tokio::spawn(async move {
+            let outgoing = client_outgoing.pop().await.unwrap();
+            assert_eq!(outgoing.payload.text_frame, r#"420["confirm",{"id":1}]"#);
+
+            let ack = SocketIoMessage::deserialize(r#"30["ok",2]"#).unwrap();
+            assert!(client_socket.handle_ack(ack));
+        });
+
+        let result: (String, i32) = socket
+            .timeout(Duration::from_secs(5))
+            .emit_with_ack("confirm", &serde_json::json!({"id": 1}))
+            .await
+            .unwrap();
+
+        assert_eq!(result, ("ok".to_string(), 2));
+        client.await.unwrap();
+
+        let result: Result<(), _> = socket
+            .timeout(Duration::from_millis(10))
+            .emit_with_ack("confirm", &1)
+            .await;
+
+        assert_eq!(result, Err(AckError::Timeout));
+        assert_eq!(socket.pending_acks_count(), 0);
+
+        socket.compress(false).emit("plain", &1).unwrap();
+        socket.volatile().emit("dropped", &2).unwrap();
+
+        assert!(outgoing.try_pop().is_some());
+        let plain = outgoing.try_pop().unwrap();
+        assert_eq!(plain.payload.text_frame, r#"42["plain",1]"#);
+        assert!(!plain.options.compress);
+        assert!(outgoing.try_pop().is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_emit_with_ack_timeout_on_full_queue() {
+        let outgoing = Arc::new(OutgoingQueue::new_with_settings(OutgoingQueueSettings {
+            high_water_mark: 1,
+            policy: QueueFullPolicy::Block,
+        }));
+        let socket = SocketIoSocket::new("sid".to_string(), "/".to_string(), outgoing.clone());
+
+        socket.emit("first", &1).unwrap();
+
+        let result: Result<(), _> = socket
+            .timeout(Duration::from_secs(1))
+            .emit_with_ack("second", &2)
+            .await;
+
+        assert_eq!(result, Err(AckError::Timeout));
+        assert_eq!(socket.pending_acks_count(), 0);
+        assert_eq!(outgoing.get_metrics().depth, 1);
+    }
+
+    #[test]
+    fn test_ack_response_escapes_event_name() {
+        let response = AckResponse::new(r#"say "hi""#, "1", Vec::new());
+        assert_eq!(response.data, r#""say \"hi\"",1"#);
+
+        let result: (String, i32) = response.get_args().unwrap();
+        assert_eq!(result, (r#"say "hi""#.to_string(), 1));
+    }
+}
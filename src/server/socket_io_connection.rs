@@ -0,0 +1,146 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    DisconnectReason, EmitOptions, OutgoingPayload, OutgoingQueue, SocketIoContract, SocketIoSocket,
+};
+
+/// Engine.IO connection. Sockets of all the namespaces the client joined share its outgoing queue
+pub struct SocketIoConnection {
+    sid: String,
+    queue: Arc<OutgoingQueue>,
+    query: HashMap<String, String>,
+    headers: HashMap<String, String>,
+    sockets: Mutex<HashMap<String, Arc<SocketIoSocket>>>,
+    close_reason: Mutex<Option<DisconnectReason>>,
+}
+
+impl SocketIoConnection {
+    pub fn new(sid: String, queue: Arc<OutgoingQueue>) -> Self {
+        Self {
+            sid,
+            queue,
+            query: HashMap::new(),
+            headers: HashMap::new(),
+            sockets: Mutex::new(HashMap::new()),
+            close_reason: Mutex::new(None),
+        }
+    }
+
+    /// Query and headers of the request which opened the connection. Copied to socket handshakes
+    pub fn with_request(
+        mut self,
+        query: HashMap<String, String>,
+        headers: HashMap<String, String>,
+    ) -> Self {
+        self.query = query;
+        self.headers = headers;
+        self
+    }
+
+    pub fn get_sid(&self) -> &str {
+        &self.sid
+    }
+
+    pub fn get_query(&self) -> &HashMap<String, String> {
+        &self.query
+    }
+
+    pub fn get_headers(&self) -> &HashMap<String, String> {
+        &self.headers
+    }
+
+    pub fn get_queue(&self) -> &Arc<OutgoingQueue> {
+        &self.queue
+    }
+
+    /// Socket connected to the namespace
+    pub fn get_socket(&self, namespace: &str) -> Option<Arc<SocketIoSocket>> {
+        self.sockets.lock().unwrap().get(namespace).cloned()
+    }
+
+    pub fn get_sockets(&self) -> Vec<Arc<SocketIoSocket>> {
+        self.sockets.lock().unwrap().values().cloned().collect()
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.get_close_reason().is_some()
+    }
+
+    pub fn get_close_reason(&self) -> Option<DisconnectReason> {
+        *self.close_reason.lock().unwrap()
+    }
+
+    /// Ping, pong, noop and close bypass high-water mark, so heartbeat keeps working with a backed up queue
+    pub fn send_contract(&self, contract: &SocketIoContract) {
+        let outgoing = OutgoingPayload::new(contract.serialize(), EmitOptions::default());
+
+        let _ = match contract {
+            SocketIoContract::Ping { .. }
+            | SocketIoContract::Pong { .. }
+            | SocketIoContract::Noop
+            | SocketIoContract::Close => self.queue.push_control(outgoing),
+            _ => self.queue.push(outgoing),
+        };
+    }
+
+    pub(crate) fn add_socket(&self, socket: Arc<SocketIoSocket>) {
+        self.sockets
+            .lock()
+            .unwrap()
+            .insert(socket.get_namespace().to_string(), socket);
+    }
+
+    pub(crate) fn remove_socket(&self, namespace: &str) -> Option<Arc<SocketIoSocket>> {
+        self.sockets.lock().unwrap().remove(namespace)
+    }
+
+    /// Returns false if connection is already closed
+    pub(crate) fn set_closed(&self, reason: DisconnectReason) -> bool {
+        let mut close_reason = self.close_reason.lock().unwrap();
+
+        if close_reason.is_some() {
+            return false;
+        }
+
+        *close_reason = Some(reason);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{OutgoingQueueSettings, QueueFullPolicy, SocketIoMessage};
+
+    #[test]
+    fn test_control_packets_bypass_high_water_mark() {
+        let queue = Arc::new(OutgoingQueue::new_with_settings(OutgoingQueueSettings {
+            high_water_mark: 1,
+            policy: QueueFullPolicy::Block,
+        }));
+        let connection = SocketIoConnection::new("sid".to_string(), queue.clone());
+
+        let message = SocketIoContract::Message(SocketIoMessage::Disconnect {
+            namespace: "/".into(),
+        });
+
+        connection.send_contract(&message);
+        connection.send_contract(&message);
+        connection.send_contract(&SocketIoContract::Ping { payload: None });
+        connection.send_contract(&SocketIoContract::Pong { payload: None });
+        connection.send_contract(&SocketIoContract::Noop);
+        connection.send_contract(&SocketIoContract::Close);
+
+        let mut result = Vec::new();
+
+        while let Some(outgoing) = queue.try_pop() {
+            result.push(outgoing.payload.text_frame);
+        }
+
+        assert_eq!(result, vec!["41", "2", "3", "6", "1"]);
+        assert_eq!(queue.get_metrics().rejected, 1);
+    }
+}
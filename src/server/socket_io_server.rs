@@ -0,0 +1,1139 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+#[cfg(any(feature = "tokio-ws", feature = "polling"))]
+use futures_util::{stream::FuturesUnordered, StreamExt};
+use tokio::sync::Notify;
+
+#[cfg(feature = "polling")]
+use crate::PollingConnection;
+#[cfg(feature = "tokio-ws")]
+use crate::{SocketIoHandshakeOpenModel, WebSocketTransport, WebSocketTransportError};
+
+use crate::{
+    DefaultParser, DisconnectReason, EmitOptions, EventRouter, LocalAdapter, OutgoingPayload,
+    OutgoingQueue, OutgoingQueueSettings, RandomSidGenerator, SidGenerator, SocketHandshake,
+    SocketIoAdapter, SocketIoConnection, SocketIoContract, SocketIoMessage, SocketIoMessageEncoder,
+    SocketIoNamespace, SocketIoParser, SocketIoSettings, SocketIoSocket,
+};
+
+pub const DEFAULT_MAX_IN_FLIGHT_HANDLERS: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShutdownError {
+    /// Transports which have not finished writing in time
+    Timeout { pending_connections: usize },
+}
+
+/// Namespaces and connections of the server. Connections are keyed by Engine.IO sid,
+/// sockets get their own random ids
+pub struct SocketIoServer {
+    settings: SocketIoSettings,
+    queue_settings: OutgoingQueueSettings,
+    max_in_flight_handlers: usize,
+    encoder: SocketIoMessageEncoder,
+    sid_generator: Box<dyn SidGenerator + Send + Sync>,
+    adapter: Arc<dyn SocketIoAdapter>,
+    namespaces: Mutex<HashMap<String, Arc<SocketIoNamespace>>>,
+    connections: Mutex<HashMap<String, Arc<SocketIoConnection>>>,
+    shutting_down: AtomicBool,
+    serving: AtomicUsize,
+    serving_finished: Notify,
+}
+
+impl SocketIoServer {
+    pub fn new(settings: SocketIoSettings) -> Self {
+        Self::new_with_parser(settings, DefaultParser)
+    }
+
+    /// Parser must be the same as the one of transports passed to the server
+    pub fn new_with_parser<P: SocketIoParser + Send + Sync + 'static>(
+        settings: SocketIoSettings,
+        parser: P,
+    ) -> Self {
+        Self {
+            settings,
+            queue_settings: OutgoingQueueSettings::default(),
+            max_in_flight_handlers: DEFAULT_MAX_IN_FLIGHT_HANDLERS,
+            encoder: Arc::new(move |message| parser.encode_to_engine_payload(message)),
+            sid_generator: Box::new(RandomSidGenerator),
+            adapter: Arc::new(LocalAdapter),
+            namespaces: Mutex::new(HashMap::new()),
+            connections: Mutex::new(HashMap::new()),
+            shutting_down: AtomicBool::new(false),
+            serving: AtomicUsize::new(0),
+            serving_finished: Notify::new(),
+        }
+    }
+
+    pub fn with_queue_settings(mut self, queue_settings: OutgoingQueueSettings) -> Self {
+        self.queue_settings = queue_settings;
+        self
+    }
+
+    /// Transport loops stop reading packets of a connection while this many of its handlers are running
+    pub fn with_max_in_flight_handlers(mut self, max_in_flight_handlers: usize) -> Self {
+        self.max_in_flight_handlers = max_in_flight_handlers;
+        self
+    }
+
+    pub fn with_sid_generator(
+        mut self,
+        sid_generator: impl SidGenerator + Send + Sync + 'static,
+    ) -> Self {
+        self.sid_generator = Box::new(sid_generator);
+        self
+    }
+
+    /// Used by namespaces added afterwards
+    pub fn with_adapter(mut self, adapter: impl SocketIoAdapter + 'static) -> Self {
+        self.adapter = Arc::new(adapter);
+        self
+    }
+
+    pub fn get_settings(&self) -> &SocketIoSettings {
+        &self.settings
+    }
+
+    pub fn add_namespace(&self, router: EventRouter) -> Arc<SocketIoNamespace> {
+        let namespace = Arc::new(SocketIoNamespace::new_with_adapter(
+            router,
+            self.adapter.clone(),
+        ));
+
+        self.namespaces
+            .lock()
+            .unwrap()
+            .insert(namespace.get_name().to_string(), namespace.clone());
+
+        namespace
+    }
+
+    pub fn get_namespace(&self, name: &str) -> Option<Arc<SocketIoNamespace>> {
+        self.namespaces.lock().unwrap().get(name).cloned()
+    }
+
+    /// Registers Engine.IO connection. Transport loops call it after the handshake
+    pub fn create_connection(&self, sid: String) -> Arc<SocketIoConnection> {
+        self.create_connection_with_request(sid, HashMap::new(), HashMap::new())
+    }
+
+    /// Query and headers of the opening request become part of socket handshakes
+    pub fn create_connection_with_request(
+        &self,
+        sid: String,
+        query: HashMap<String, String>,
+        headers: HashMap<String, String>,
+    ) -> Arc<SocketIoConnection> {
+        let queue = Arc::new(OutgoingQueue::new_with_settings(
+            self.queue_settings.clone(),
+        ));
+        let connection =
+            Arc::new(SocketIoConnection::new(sid.clone(), queue).with_request(query, headers));
+
+        self.connections
+            .lock()
+            .unwrap()
+            .insert(sid, connection.clone());
+
+        connection
+    }
+
+    pub fn get_connection(&self, sid: &str) -> Option<Arc<SocketIoConnection>> {
+        self.connections.lock().unwrap().get(sid).cloned()
+    }
+
+    pub fn connections_count(&self) -> usize {
+        self.connections.lock().unwrap().len()
+    }
+
+    pub async fn handle_contract(
+        &self,
+        connection: &Arc<SocketIoConnection>,
+        contract: SocketIoContract,
+    ) {
+        match contract {
+            SocketIoContract::Message(message) => self.handle_message(connection, message).await,
+            SocketIoContract::Ping { payload } => {
+                connection.send_contract(&SocketIoContract::Pong { payload })
+            }
+            SocketIoContract::Close => {
+                self.close_connection(connection.get_sid(), DisconnectReason::TransportClose)
+                    .await
+            }
+            _ => {}
+        }
+    }
+
+    async fn handle_message(&self, connection: &Arc<SocketIoConnection>, message: SocketIoMessage) {
+        match message {
+            SocketIoMessage::Connect {
+                namespace, auth, ..
+            } => {
+                self.connect(
+                    connection,
+                    namespace.as_str(),
+                    auth.as_ref().map(|auth| auth.as_str()),
+                )
+                .await
+            }
+            SocketIoMessage::Disconnect { namespace } => {
+                self.remove_socket(
+                    connection,
+                    namespace.as_str(),
+                    DisconnectReason::ClientNamespaceDisconnect,
+                )
+                .await
+            }
+            message => {
+                let socket = connection.get_socket(message.get_namespace());
+                let namespace = self.get_namespace(message.get_namespace());
+
+                if let (Some(socket), Some(namespace)) = (socket, namespace) {
+                    // Unknown events and bad arguments are not a reason to drop the client
+                    let _ = namespace.handle_message(socket, message).await;
+                }
+            }
+        }
+    }
+
+    async fn connect(
+        &self,
+        connection: &Arc<SocketIoConnection>,
+        namespace_name: &str,
+        auth: Option<&str>,
+    ) {
+        let namespace = match self.get_namespace(namespace_name) {
+            Some(namespace) => namespace,
+            None => {
+                self.send_connect_error(connection, namespace_name, "Invalid namespace");
+                return;
+            }
+        };
+
+        if connection.get_socket(namespace_name).is_some() {
+            return;
+        }
+
+        let mut handshake = SocketHandshake::new(connection.get_sid().to_string());
+        handshake.query = connection.get_query().clone();
+        handshake.headers = connection.get_headers().clone();
+
+        if let Some(auth) = auth {
+            match serde_json::from_str::<serde_json::Value>(auth) {
+                Ok(auth) if auth.is_object() => handshake.auth = auth,
+                _ => {
+                    self.send_connect_error(connection, namespace_name, "Invalid auth");
+                    return;
+                }
+            }
+        }
+
+        let socket = Arc::new(
+            SocketIoSocket::new_with_encoder(
+                self.sid_generator.generate_sid(),
+                namespace_name.to_string(),
+                connection.get_queue().clone(),
+                self.encoder.clone(),
+            )
+            .with_connection(connection)
+            .with_handshake(handshake),
+        );
+
+        namespace.add_socket(socket.clone());
+        connection.add_socket(socket.clone());
+
+        let _ = socket.send_message(&SocketIoMessage::Connect {
+            namespace: namespace_name.to_string().into(),
+            sid: Some(socket.get_id().to_string().into()),
+            auth: None,
+        });
+
+        namespace.get_router().handle_connect(socket).await;
+    }
+
+    fn send_connect_error(
+        &self,
+        connection: &SocketIoConnection,
+        namespace_name: &str,
+        message: &'static str,
+    ) {
+        let message = SocketIoMessage::ConnectError {
+            namespace: namespace_name.to_string().into(),
+            message: message.into(),
+        };
+
+        if let Ok(payload) = (self.encoder)(&message) {
+            let _ = connection
+                .get_queue()
+                .push(OutgoingPayload::new(payload, EmitOptions::default()));
+        }
+    }
+
+    /// Sends namespace `Disconnect` to the client. Connection stays open
+    pub async fn disconnect(&self, socket: &SocketIoSocket) {
+        let connection = match socket.get_connection() {
+            Some(connection) => connection,
+            None => return,
+        };
+
+        let _ = socket.send_message(&SocketIoMessage::Disconnect {
+            namespace: socket.get_namespace().to_string().into(),
+        });
+
+        self.remove_socket(
+            &connection,
+            socket.get_namespace(),
+            DisconnectReason::ServerNamespaceDisconnect,
+        )
+        .await;
+    }
+
+    /// Disconnects all the sockets of the connection. Transport is closed once queued payloads are written
+    pub async fn close_connection(&self, sid: &str, reason: DisconnectReason) {
+        let connection = match self.connections.lock().unwrap().remove(sid) {
+            Some(connection) => connection,
+            None => return,
+        };
+
+        if !connection.set_closed(reason) {
+            return;
+        }
+
+        for socket in connection.get_sockets() {
+            self.remove_socket(&connection, socket.get_namespace(), reason)
+                .await;
+        }
+
+        connection.get_queue().close();
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+
+    /// Every socket gets namespace `Disconnect`, then its transport gets Engine.IO `Close`.
+    /// Outstanding acks fail with [`crate::AckError::ConnectionClosed`], pending polling requests are released.
+    /// Waits for the transports to write everything queued, new transports are closed right away
+    pub async fn shutdown(&self, timeout: Duration) -> Result<(), ShutdownError> {
+        self.shutting_down.store(true, Ordering::SeqCst);
+
+        let connections: Vec<_> = self.connections.lock().unwrap().values().cloned().collect();
+
+        for connection in connections {
+            for socket in connection.get_sockets() {
+                let _ = socket.send_message(&SocketIoMessage::Disconnect {
+                    namespace: socket.get_namespace().to_string().into(),
+                });
+            }
+
+            self.close_connection(connection.get_sid(), DisconnectReason::ServerShuttingDown)
+                .await;
+        }
+
+        let wait = async {
+            loop {
+                let notified = self.serving_finished.notified();
+
+                if self.serving.load(Ordering::SeqCst) == 0 {
+                    return;
+                }
+
+                notified.await;
+            }
+        };
+
+        match tokio::time::timeout(timeout, wait).await {
+            Ok(_) => Ok(()),
+            Err(_) => Err(ShutdownError::Timeout {
+                pending_connections: self.serving.load(Ordering::SeqCst),
+            }),
+        }
+    }
+
+    async fn remove_socket(
+        &self,
+        connection: &SocketIoConnection,
+        namespace_name: &str,
+        reason: DisconnectReason,
+    ) {
+        let socket = match connection.remove_socket(namespace_name) {
+            Some(socket) => socket,
+            None => return,
+        };
+
+        match self.get_namespace(namespace_name) {
+            Some(namespace) => namespace.detach_socket(socket, reason).await,
+            None => socket.fail_pending_acks(),
+        }
+    }
+
+    /// Serves accepted WebSocket connection until it is closed: handshake, pings, incoming packets and outgoing queue.
+    /// Handlers run concurrently with the transport, so they may wait for acks of the client.
+    /// Packets are not read while [`Self::with_max_in_flight_handlers`] handlers are running
+    #[cfg(feature = "tokio-ws")]
+    pub async fn serve_web_socket<S, P>(&self, mut transport: WebSocketTransport<S, P>)
+    where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+        P: SocketIoParser,
+    {
+        if self.is_shutting_down() {
+            let _ = transport.close().await;
+            return;
+        }
+
+        let _serving = ServingGuard::new(self);
+        let sid = self.sid_generator.generate_sid();
+
+        let mut open_model = SocketIoHandshakeOpenModel::from_settings(sid.clone(), &self.settings);
+        open_model.upgrades.clear();
+
+        let open = SocketIoContract::Open(open_model);
+
+        if transport.send_contract(&open).await.is_err() {
+            return;
+        }
+
+        let connection = self.create_connection_with_request(
+            sid.clone(),
+            transport.get_query().clone(),
+            transport.get_headers().clone(),
+        );
+        let queue = connection.get_queue().clone();
+
+        let mut heartbeat = Heartbeat::new(&self.settings);
+        let mut handlers = FuturesUnordered::new();
+        let max_handlers = self.max_in_flight_handlers;
+
+        let reason = loop {
+            tokio::select! {
+                outgoing = queue.pop() => match outgoing {
+                    Some(outgoing) => {
+                        if transport.send_outgoing(&outgoing).await.is_err() {
+                            break DisconnectReason::TransportError;
+                        }
+                    }
+                    // Queue is closed by the server: slow client, socket disconnect with close or shutdown
+                    None => break DisconnectReason::ForcedServerClose,
+                },
+                received = transport.receive(), if handlers.len() < max_handlers => match received {
+                    Ok(Some(SocketIoContract::Pong { .. })) => heartbeat.pong_received(),
+                    Ok(Some(contract)) => handlers.push(self.handle_contract(&connection, contract)),
+                    Ok(None) => break DisconnectReason::TransportClose,
+                    Err(
+                        WebSocketTransportError::WebSocket(_) | WebSocketTransportError::Encode(_),
+                    ) => break DisconnectReason::TransportError,
+                    Err(WebSocketTransportError::Decode(_)) => {
+                        break DisconnectReason::ParseError
+                    }
+                },
+                Some(()) = handlers.next() => {}
+                _ = tokio::time::sleep_until(heartbeat.ping_at) => {
+                    if !heartbeat.ping(&connection) {
+                        break DisconnectReason::PingTimeout;
+                    }
+                }
+            }
+        };
+
+        // No-op if the connection is already closed by the server
+        self.close_connection(&sid, reason).await;
+
+        if is_server_side_close(connection.get_close_reason()) {
+            while let Some(outgoing) = queue.try_pop() {
+                if transport.send_outgoing(&outgoing).await.is_err() {
+                    break;
+                }
+            }
+
+            let _ = transport.send_contract(&SocketIoContract::Close).await;
+        }
+
+        let _ = transport.close().await;
+
+        while handlers.next().await.is_some() {}
+    }
+
+    /// Serves polling connection until the session is closed. Payloads are passed to the session
+    /// once the client has taken the previous ones. Pings and handlers work as in [`Self::serve_web_socket`]
+    #[cfg(feature = "polling")]
+    pub async fn serve_polling<P: SocketIoParser>(&self, mut polling: PollingConnection<P>) {
+        if self.is_shutting_down() {
+            polling.close();
+            return;
+        }
+
+        let _serving = ServingGuard::new(self);
+        let sid = polling.sid.clone();
+        let connection = self.create_connection_with_request(
+            sid.clone(),
+            polling.query.clone(),
+            polling.headers.clone(),
+        );
+        let queue = connection.get_queue().clone();
+
+        let mut incoming = std::mem::replace(
+            &mut polling.incoming,
+            tokio::sync::mpsc::unbounded_channel().1,
+        );
+
+        let mut heartbeat = Heartbeat::new(&self.settings);
+        let mut handlers = FuturesUnordered::new();
+        let max_handlers = self.max_in_flight_handlers;
+
+        let reason = {
+            let writer = crate::run_polling_writer(&queue, &polling);
+            tokio::pin!(writer);
+
+            loop {
+                tokio::select! {
+                    contract = incoming.recv(), if handlers.len() < max_handlers => match contract {
+                        Some(SocketIoContract::Pong { .. }) => heartbeat.pong_received(),
+                        Some(contract) => handlers.push(self.handle_contract(&connection, contract)),
+                        None => break DisconnectReason::TransportClose,
+                    },
+                    Some(()) = handlers.next() => {}
+                    _ = tokio::time::sleep_until(heartbeat.ping_at) => {
+                        if !heartbeat.ping(&connection) {
+                            break DisconnectReason::PingTimeout;
+                        }
+                    }
+                    _ = &mut writer => break DisconnectReason::ForcedServerClose,
+                }
+            }
+        };
+
+        self.close_connection(&sid, reason).await;
+
+        if is_server_side_close(connection.get_close_reason()) {
+            polling.close();
+        }
+
+        while handlers.next().await.is_some() {}
+    }
+}
+
+/// Engine.IO pings of a transport loop. Client must answer each ping within `ping_timeout`
+#[cfg(any(feature = "tokio-ws", feature = "polling"))]
+struct Heartbeat {
+    ping_interval: Duration,
+    ping_timeout: Duration,
+    ping_at: tokio::time::Instant,
+    waiting_pong: bool,
+}
+
+#[cfg(any(feature = "tokio-ws", feature = "polling"))]
+impl Heartbeat {
+    fn new(settings: &SocketIoSettings) -> Self {
+        Self {
+            ping_interval: settings.ping_interval,
+            ping_timeout: settings.ping_timeout,
+            ping_at: tokio::time::Instant::now() + settings.ping_interval,
+            waiting_pong: false,
+        }
+    }
+
+    fn pong_received(&mut self) {
+        self.waiting_pong = false;
+        self.ping_at = tokio::time::Instant::now() + self.ping_interval;
+    }
+
+    /// Called at `ping_at`. Returns false if the previous ping is not answered
+    fn ping(&mut self, connection: &SocketIoConnection) -> bool {
+        if self.waiting_pong {
+            return false;
+        }
+
+        connection.send_contract(&SocketIoContract::Ping { payload: None });
+        self.waiting_pong = true;
+        self.ping_at = tokio::time::Instant::now() + self.ping_timeout;
+        true
+    }
+}
+
+/// Counts running transport loops for [`SocketIoServer::shutdown`]
+#[cfg(any(feature = "tokio-ws", feature = "polling"))]
+struct ServingGuard<'s> {
+    server: &'s SocketIoServer,
+}
+
+#[cfg(any(feature = "tokio-ws", feature = "polling"))]
+impl<'s> ServingGuard<'s> {
+    fn new(server: &'s SocketIoServer) -> Self {
+        server.serving.fetch_add(1, Ordering::SeqCst);
+        Self { server }
+    }
+}
+
+#[cfg(any(feature = "tokio-ws", feature = "polling"))]
+impl Drop for ServingGuard<'_> {
+    fn drop(&mut self) {
+        self.server.serving.fetch_sub(1, Ordering::SeqCst);
+        self.server.serving_finished.notify_waiters();
+    }
+}
+
+#[cfg(any(feature = "tokio-ws", feature = "polling"))]
+fn is_server_side_close(reason: Option<DisconnectReason>) -> bool {
+    !matches!(
+        reason,
+        Some(DisconnectReason::TransportClose) | Some(DisconnectReason::TransportError)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::AckError;
+
+    fn create_server() -> (SocketIoServer, Arc<Mutex<Vec<DisconnectReason>>>) {
+        create_server_with_settings(SocketIoSettings::default())
+    }
+
+    fn create_server_with_settings(
+        settings: SocketIoSettings,
+    ) -> (SocketIoServer, Arc<Mutex<Vec<DisconnectReason>>>) {
+        let server = SocketIoServer::new(settings);
+        let reasons = Arc::new(Mutex::new(Vec::new()));
+
+        let mut router = EventRouter::new("/chat");
+        let reasons_copy = reasons.clone();
+        router.on_disconnect(move |_, reason| {
+            let reasons = reasons_copy.clone();
+            async move {
+                reasons.lock().unwrap().push(reason);
+            }
+        });
+
+        server.add_namespace(router);
+        (server, reasons)
+    }
+
+    fn connect_message(namespace: &str) -> SocketIoContract {
+        SocketIoContract::Message(SocketIoMessage::Connect {
+            namespace: namespace.to_string().into(),
+            sid: None,
+            auth: None,
+        })
+    }
+
+    fn pop_all(connection: &SocketIoConnection) -> Vec<String> {
+        let mut result = Vec::new();
+
+        while let Some(outgoing) = connection.get_queue().try_pop() {
+            result.push(outgoing.payload.text_frame);
+        }
+
+        result
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_reasons() {
+        let (server, reasons) = create_server();
+        let connection = server.create_connection("sid".to_string());
+
+        server
+            .handle_contract(&connection, connect_message("/chat"))
+            .await;
+        server
+            .handle_contract(&connection, connect_message("/unknown"))
+            .await;
+
+        let socket = connection.get_socket("/chat").unwrap();
+        server.disconnect(&socket).await;
+
+        assert_eq!(
+            pop_all(&connection),
+            vec![
+                format!(r#"40/chat,{{"sid":"{}"}}"#, socket.get_id()),
+                r#"44/unknown,{"message":"Invalid namespace"}"#.to_string(),
+                "41/chat,".to_string(),
+            ]
+        );
+
+        server
+            .handle_contract(&connection, connect_message("/chat"))
+            .await;
+        server
+            .handle_contract(
+                &connection,
+                SocketIoContract::Message(SocketIoMessage::Disconnect {
+                    namespace: "/chat".into(),
+                }),
+            )
+            .await;
+
+        server
+            .handle_contract(&connection, connect_message("/chat"))
+            .await;
+
+        let socket = connection.get_socket("/chat").unwrap();
+        let ack = tokio::spawn(async move {
+            socket
+                .timeout(Duration::from_secs(5))
+                .emit_with_ack::<_, ()>("confirm", &1)
+                .await
+        });
+        tokio::task::yield_now().await;
+
+        server
+            .close_connection("sid", DisconnectReason::ForcedServerClose)
+            .await;
+
+        assert_eq!(ack.await.unwrap(), Err(AckError::ConnectionClosed));
+        assert!(connection.get_queue().is_closed());
+        assert_eq!(server.connections_count(), 0);
+        assert_eq!(server.get_namespace("/chat").unwrap().sockets_count(), 0);
+
+        assert_eq!(
+            *reasons.lock().unwrap(),
+            vec![
+                DisconnectReason::ServerNamespaceDisconnect,
+                DisconnectReason::ClientNamespaceDisconnect,
+                DisconnectReason::ForcedServerClose,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_socket_id_and_auth() {
+        let (server, _) = create_server();
+        server.add_namespace(EventRouter::new("/"));
+        let connection = server.create_connection("sid".to_string());
+
+        server
+            .handle_contract(
+                &connection,
+                SocketIoContract::Message(
+                    SocketIoMessage::deserialize(r#"0/chat,{"token":"abc"}"#).unwrap(),
+                ),
+            )
+            .await;
+        server
+            .handle_contract(&connection, connect_message("/"))
+            .await;
+
+        let chat_socket = connection.get_socket("/chat").unwrap();
+        let main_socket = connection.get_socket("/").unwrap();
+
+        assert_ne!(chat_socket.get_id(), "sid");
+        assert_ne!(chat_socket.get_id(), main_socket.get_id());
+        assert_eq!(chat_socket.get_handshake().sid, "sid");
+        assert_eq!(
+            chat_socket.get_handshake().auth,
+            serde_json::json!({"token": "abc"})
+        );
+        assert_eq!(main_socket.get_handshake().auth, serde_json::json!({}));
+
+        server
+            .handle_contract(
+                &connection,
+                SocketIoContract::Message(SocketIoMessage::Disconnect {
+                    namespace: "/chat".into(),
+                }),
+            )
+            .await;
+        pop_all(&connection);
+
+        server
+            .handle_contract(
+                &connection,
+                SocketIoContract::Message(SocketIoMessage::Connect {
+                    namespace: "/chat".into(),
+                    sid: None,
+                    auth: Some(r#""abc""#.into()),
+                }),
+            )
+            .await;
+
+        assert!(connection.get_socket("/chat").is_none());
+        assert_eq!(
+            pop_all(&connection),
+            vec![r#"44/chat,{"message":"Invalid auth"}"#]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_sockets_of_room() {
+        let (server, reasons) = create_server();
+        let connection = server.create_connection("sid".to_string());
+
+        server
+            .handle_contract(&connection, connect_message("/chat"))
+            .await;
+        connection.get_socket("/chat").unwrap().join("user:1");
+        pop_all(&connection);
+
+        let namespace = server.get_namespace("/chat").unwrap();
+        namespace.in_room("user:1").disconnect_sockets(false).await;
+
+        assert!(connection.get_socket("/chat").is_none());
+        assert_eq!(pop_all(&connection), vec!["41/chat,"]);
+        assert_eq!(
+            *reasons.lock().unwrap(),
+            vec![DisconnectReason::ServerNamespaceDisconnect]
+        );
+
+        // Client is able to connect again
+        server
+            .handle_contract(&connection, connect_message("/chat"))
+            .await;
+        assert_eq!(namespace.sockets_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_socket_extensions() {
+        #[derive(Debug, Clone, PartialEq)]
+        struct UserId(u64);
+
+        let server = SocketIoServer::new(SocketIoSettings::default());
+        let events = Arc::new(Mutex::new(Vec::new()));
+
+        let mut router = EventRouter::new("/chat");
+        router.on_connect(|socket| async move {
+            socket.insert_extension(UserId(42));
+        });
+
+        let events_copy = events.clone();
+        router.on("whoami", move |socket, _: (), _| {
+            let events = events_copy.clone();
+            async move {
+                let user_id = socket.get_extension::<UserId>().unwrap();
+                events.lock().unwrap().push(format!("event {}", user_id.0));
+            }
+        });
+
+        let events_copy = events.clone();
+        router.on_disconnect(move |socket, _| {
+            let events = events_copy.clone();
+            async move {
+                let user_id = socket.get_extension::<UserId>().unwrap();
+                events
+                    .lock()
+                    .unwrap()
+                    .push(format!("disconnect {}", user_id.0));
+            }
+        });
+
+        let namespace = server.add_namespace(router);
+        let connection = server.create_connection("sid".to_string());
+
+        server
+            .handle_contract(&connection, connect_message("/chat"))
+            .await;
+
+        let sockets = namespace.select().fetch_sockets().await;
+        assert_eq!(sockets[0].extensions.get::<UserId>(), Some(&UserId(42)));
+
+        server
+            .handle_contract(
+                &connection,
+                SocketIoContract::Message(
+                    SocketIoMessage::deserialize(r#"2/chat,["whoami"]"#).unwrap(),
+                ),
+            )
+            .await;
+        server
+            .close_connection("sid", DisconnectReason::TransportClose)
+            .await;
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec!["event 42".to_string(), "disconnect 42".to_string()]
+        );
+    }
+
+    #[cfg(feature = "tokio-ws")]
+    async fn connect_web_socket(
+        server: Arc<SocketIoServer>,
+    ) -> (
+        WebSocketTransport<tokio::net::TcpStream>,
+        tokio::task::JoinHandle<()>,
+    ) {
+        use tokio::net::{TcpListener, TcpStream};
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let serving = tokio::spawn(async move {
+            let (tcp_stream, _) = listener.accept().await.unwrap();
+            let stream = tokio_tungstenite::accept_async(tcp_stream).await.unwrap();
+            server
+                .serve_web_socket(WebSocketTransport::new(stream))
+                .await;
+        });
+
+        let tcp_stream = TcpStream::connect(addr).await.unwrap();
+        let url = format!("ws://{}/socket.io/?EIO=4&transport=websocket", addr);
+        let (stream, _) = tokio_tungstenite::client_async(url, tcp_stream)
+            .await
+            .unwrap();
+        let mut client = WebSocketTransport::new(stream);
+
+        match client.receive().await.unwrap() {
+            Some(SocketIoContract::Open(model)) => assert!(model.upgrades.is_empty()),
+            _ => panic!("Open is expected"),
+        }
+
+        client
+            .send_contract(&connect_message("/chat"))
+            .await
+            .unwrap();
+
+        match client.receive().await.unwrap() {
+            Some(SocketIoContract::Message(SocketIoMessage::Connect { namespace, .. })) => {
+                assert_eq!(namespace.as_str(), "/chat")
+            }
+            _ => panic!("Connect is expected"),
+        }
+
+        (client, serving)
+    }
+
+    #[cfg(feature = "tokio-ws")]
+    #[tokio::test]
+    async fn test_web_socket_transport_close() {
+        let (server, reasons) = create_server();
+        let server = Arc::new(server);
+
+        let (mut client, serving) = connect_web_socket(server.clone()).await;
+
+        client.close().await.unwrap();
+        serving.await.unwrap();
+
+        assert_eq!(
+            *reasons.lock().unwrap(),
+            vec![DisconnectReason::TransportClose]
+        );
+        assert_eq!(server.connections_count(), 0);
+    }
+
+    #[cfg(feature = "tokio-ws")]
+    #[tokio::test]
+    async fn test_web_socket_handler_waits_for_ack() {
+        let server = SocketIoServer::new(SocketIoSettings::default());
+        let (results_sender, mut results) = tokio::sync::mpsc::unbounded_channel();
+
+        let mut router = EventRouter::new("/chat");
+        router.on("ask", move |socket, _: (), _| {
+            let results_sender = results_sender.clone();
+            async move {
+                let result: Result<(String, i32), _> = socket
+                    .timeout(Duration::from_secs(5))
+                    .emit_with_ack("confirm", &1)
+                    .await;
+                results_sender.send(result).unwrap();
+            }
+        });
+        server.add_namespace(router);
+
+        let (mut client, serving) = connect_web_socket(Arc::new(server)).await;
+
+        client
+            .send_contract(&SocketIoContract::Message(
+                SocketIoMessage::deserialize(r#"2/chat,["ask"]"#).unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        let request = client.receive().await.unwrap().unwrap();
+        assert_eq!(request.serialize().text_frame, r#"42/chat,0["confirm",1]"#);
+
+        client
+            .send_contract(&SocketIoContract::Message(
+                SocketIoMessage::deserialize(r#"3/chat,0["ok",2]"#).unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(results.recv().await.unwrap(), Ok(("ok".to_string(), 2)));
+
+        client.close().await.unwrap();
+        serving.await.unwrap();
+    }
+
+    #[cfg(feature = "tokio-ws")]
+    #[tokio::test]
+    async fn test_web_socket_stops_reading_at_max_in_flight_handlers() {
+        let server =
+            SocketIoServer::new(SocketIoSettings::default()).with_max_in_flight_handlers(1);
+        let (events_sender, mut events) = tokio::sync::mpsc::unbounded_channel();
+        let release = Arc::new(Notify::new());
+
+        let mut router = EventRouter::new("/chat");
+        let wait_sender = events_sender.clone();
+        let wait_release = release.clone();
+        router.on("wait", move |_, _: (), _| {
+            let events_sender = wait_sender.clone();
+            let release = wait_release.clone();
+            async move {
+                release.notified().await;
+                events_sender.send("wait").unwrap();
+            }
+        });
+        router.on("count", move |_, _: (), _| {
+            let events_sender = events_sender.clone();
+            async move {
+                events_sender.send("count").unwrap();
+            }
+        });
+        server.add_namespace(router);
+
+        let (mut client, serving) = connect_web_socket(Arc::new(server)).await;
+
+        for event in [r#"2/chat,["wait"]"#, r#"2/chat,["count"]"#] {
+            client
+                .send_contract(&SocketIoContract::Message(
+                    SocketIoMessage::deserialize(event).unwrap(),
+                ))
+                .await
+                .unwrap();
+        }
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(events.try_recv().is_err());
+
+        release.notify_one();
+        assert_eq!(events.recv().await.unwrap(), "wait");
+        assert_eq!(events.recv().await.unwrap(), "count");
+
+        client.close().await.unwrap();
+        serving.await.unwrap();
+    }
+
+    #[cfg(feature = "tokio-ws")]
+    #[tokio::test]
+    async fn test_web_socket_shutdown_packet_order() {
+        let (server, reasons) = create_server();
+        let server = Arc::new(server);
+
+        let (mut client, serving) = connect_web_socket(server.clone()).await;
+
+        let namespace = server.get_namespace("/chat").unwrap();
+        namespace.broadcast().emit("bye", &1);
+
+        server.shutdown(Duration::from_secs(5)).await.unwrap();
+        serving.await.unwrap();
+
+        let mut received = Vec::new();
+
+        while let Some(contract) = client.receive().await.unwrap() {
+            received.push(contract.serialize().text_frame);
+        }
+
+        assert_eq!(received, vec![r#"42/chat,["bye",1]"#, "41/chat,", "1"]);
+        assert_eq!(
+            *reasons.lock().unwrap(),
+            vec![DisconnectReason::ServerShuttingDown]
+        );
+    }
+
+    #[cfg(feature = "polling")]
+    async fn request(router: &axum::Router, method: &str, uri: &str, body: &str) -> String {
+        use axum::{body::Body, http::Request};
+        use tower::ServiceExt;
+
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::from(body.to_string()))
+            .unwrap();
+
+        let response = router.clone().oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    /// Returns uri of the session connected to `/chat`
+    #[cfg(feature = "polling")]
+    async fn connect_polling(
+        server: Arc<SocketIoServer>,
+        router: &axum::Router,
+        connections: &mut tokio::sync::mpsc::UnboundedReceiver<crate::PollingConnection>,
+    ) -> (String, tokio::task::JoinHandle<()>) {
+        let sid = match SocketIoContract::deserialize(
+            &request(router, "GET", "/socket.io/?EIO=4&transport=polling", "").await,
+        )
+        .unwrap()
+        {
+            SocketIoContract::Open(model) => model.sid,
+            _ => panic!("Open packet is expected"),
+        };
+
+        let polling = connections.recv().await.unwrap();
+        let serving = tokio::spawn(async move { server.serve_polling(polling).await });
+
+        let uri = format!("/socket.io/?EIO=4&transport=polling&sid={}", sid);
+        request(router, "POST", &uri, "40/chat,").await;
+        assert!(request(router, "GET", &uri, "")
+            .await
+            .starts_with("40/chat,"));
+
+        (uri, serving)
+    }
+
+    #[cfg(feature = "polling")]
+    #[tokio::test]
+    async fn test_polling_shutdown_releases_request() {
+        let (server, reasons) = create_server();
+        let server = Arc::new(server);
+
+        let (transport, mut connections) = crate::PollingTransport::new(Default::default());
+        let router = transport.router();
+
+        let (uri, serving) = connect_polling(server.clone(), &router, &mut connections).await;
+
+        let pending_router = router.clone();
+        let pending_uri = uri.clone();
+        let pending =
+            tokio::spawn(async move { request(&pending_router, "GET", &pending_uri, "").await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        server.shutdown(Duration::from_secs(5)).await.unwrap();
+        serving.await.unwrap();
+
+        assert_eq!(pending.await.unwrap(), "41/chat,\x1e1");
+        assert_eq!(
+            *reasons.lock().unwrap(),
+            vec![DisconnectReason::ServerShuttingDown]
+        );
+    }
+
+    #[cfg(feature = "polling")]
+    #[tokio::test]
+    async fn test_polling_ping_timeout() {
+        let (server, reasons) = create_server_with_settings(SocketIoSettings {
+            ping_interval: Duration::from_millis(100),
+            ping_timeout: Duration::from_millis(100),
+            ..Default::default()
+        });
+        let server = Arc::new(server);
+
+        let (transport, mut connections) = crate::PollingTransport::new(Default::default());
+        let router = transport.router();
+
+        let (uri, serving) = connect_polling(server, &router, &mut connections).await;
+
+        assert_eq!(request(&router, "GET", &uri, "").await, "2");
+        request(&router, "POST", &uri, "3").await;
+
+        assert_eq!(request(&router, "GET", &uri, "").await, "2");
+        serving.await.unwrap();
+
+        assert_eq!(
+            *reasons.lock().unwrap(),
+            vec![DisconnectReason::PingTimeout]
+        );
+    }
+}
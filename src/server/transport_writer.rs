@@ -0,0 +1,22 @@
+use crate::{OutgoingQueue, PollingConnection, SocketIoParser};
+
+/// Moves queued payloads to the polling session once the client has taken the previous ones,
+/// so payloads of a slow client stay in the queue
+pub async fn run_polling_writer<P: SocketIoParser>(
+    queue: &OutgoingQueue,
+    connection: &PollingConnection<P>,
+) {
+    while let Some(outgoing) = queue.pop().await {
+        connection.drained().await;
+
+        if connection.is_closed() {
+            return;
+        }
+
+        connection.send_outgoing(&outgoing);
+
+        while let Some(outgoing) = queue.try_pop() {
+            connection.send_outgoing(&outgoing);
+        }
+    }
+}
@@ -0,0 +1,418 @@
+use ciborium::Value;
+
+use crate::{
+    decode_value_packet, encode_value_packet, PacketValue, SocketIoDecodeError,
+    SocketIoDecodeLimits, SocketIoEncodeError, SocketIoMessage, SocketIoPacketDecoder,
+    SocketIoParser, SocketIoPayload, PACKET_TYPE_BINARY_ACK, PACKET_TYPE_BINARY_EVENT,
+};
+
+/// Each packet is a CBOR map `{type, data, nsp, id}` sent as a single binary frame.
+/// `data` of events and acks is an array `[event_name, args...]`, binary attachments are inline byte strings.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CborParser;
+
+impl SocketIoParser for CborParser {
+    type Decoder = CborPacketDecoder;
+
+    fn encode(&self, message: &SocketIoMessage) -> Result<SocketIoPayload, SocketIoEncodeError> {
+        let mut result = SocketIoPayload::new();
+        result.binary_frames.push(Self::encode_packet(message)?);
+        Ok(result)
+    }
+
+    fn create_decoder(&self, limits: SocketIoDecodeLimits) -> Self::Decoder {
+        CborPacketDecoder { limits }
+    }
+}
+
+pub struct CborPacketDecoder {
+    limits: SocketIoDecodeLimits,
+}
+
+impl SocketIoPacketDecoder for CborPacketDecoder {
+    fn decode_text(&mut self, text: &str) -> Result<Option<SocketIoMessage>, SocketIoDecodeError> {
+        Err(SocketIoDecodeError::InvalidPacket(format!(
+            "Text frames are not supported by cbor parser [{}]",
+            text
+        )))
+    }
+
+    fn decode_binary(
+        &mut self,
+        data: Vec<u8>,
+    ) -> Result<Option<SocketIoMessage>, SocketIoDecodeError> {
+        self.limits.check_binary_frame(&data)?;
+
+        let message = CborParser::decode_packet_with_limits(&data, &self.limits)?;
+        self.limits.check_message(&message)?;
+        Ok(Some(message))
+    }
+}
+
+impl CborParser {
+    pub fn encode_packet(message: &SocketIoMessage) -> Result<Vec<u8>, SocketIoEncodeError> {
+        encode_value_packet::<Value>(message)
+    }
+
+    /// Decodes with default [`SocketIoDecodeLimits`]
+    pub fn decode_packet(src: &[u8]) -> Result<SocketIoMessage, SocketIoDecodeError> {
+        Self::decode_packet_with_limits(src, &SocketIoDecodeLimits::default())
+    }
+
+    pub fn decode_packet_with_limits(
+        src: &[u8],
+        limits: &SocketIoDecodeLimits,
+    ) -> Result<SocketIoMessage, SocketIoDecodeError> {
+        decode_value_packet::<Value>(src, limits)
+    }
+}
+
+impl PacketValue for Value {
+    const BINARY_EVENT_TYPE: u64 = PACKET_TYPE_BINARY_EVENT;
+    const BINARY_ACK_TYPE: u64 = PACKET_TYPE_BINARY_ACK;
+
+    fn from_u64(value: u64) -> Self {
+        Value::from(value)
+    }
+
+    fn from_i64(value: i64) -> Self {
+        Value::from(value)
+    }
+
+    fn from_bool(value: bool) -> Self {
+        Value::from(value)
+    }
+
+    fn from_text(value: &str) -> Self {
+        Value::from(value)
+    }
+
+    fn from_bytes(value: Vec<u8>) -> Self {
+        Value::Bytes(value)
+    }
+
+    fn from_map(fields: Vec<(Self, Self)>) -> Self {
+        Value::Map(fields)
+    }
+
+    fn as_u64(&self) -> Option<u64> {
+        u64::try_from(self.as_integer()?).ok()
+    }
+
+    fn as_i64(&self) -> Option<i64> {
+        i64::try_from(self.as_integer()?).ok()
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        Value::as_bool(self)
+    }
+
+    fn as_text(&self) -> Option<&str> {
+        Value::as_text(self)
+    }
+
+    fn as_map(&self) -> Option<&Vec<(Self, Self)>> {
+        Value::as_map(self)
+    }
+
+    fn as_bytes_mut(&mut self) -> Option<&mut Vec<u8>> {
+        Value::as_bytes_mut(self)
+    }
+
+    fn as_array_mut(&mut self) -> Option<&mut Vec<Self>> {
+        Value::as_array_mut(self)
+    }
+
+    fn as_map_mut(&mut self) -> Option<&mut Vec<(Self, Self)>> {
+        Value::as_map_mut(self)
+    }
+
+    fn into_array(self) -> Option<Vec<Self>> {
+        Value::into_array(self).ok()
+    }
+
+    fn into_map(self) -> Option<Vec<(Self, Self)>> {
+        Value::into_map(self).ok()
+    }
+
+    fn write(&self) -> Vec<u8> {
+        let mut result = Vec::new();
+        ciborium::into_writer(self, &mut result).unwrap();
+        result
+    }
+
+    fn read(src: &[u8]) -> Result<Self, String> {
+        ciborium::from_reader(src).map_err(|err| format!("Invalid cbor: {}", err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(message: &SocketIoMessage) -> SocketIoMessage {
+        let payload = CborParser.encode(message).unwrap();
+
+        assert!(payload.text_frame.is_empty());
+        assert_eq!(payload.binary_frames.len(), 1);
+
+        let mut decoder = CborParser.create_decoder(SocketIoDecodeLimits::default());
+
+        decoder
+            .decode_binary(payload.binary_frames.into_iter().next().unwrap())
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_encoded_bytes() {
+        let message = SocketIoMessage::Event {
+            namespace: "/".into(),
+            event_name: "foo".into(),
+            data: "".into(),
+            rest_args: Vec::new(),
+            ack: None,
+        };
+
+        // { "type": 2, "data": ["foo"], "nsp": "/" }
+        let expected = [
+            0xa3, 0x64, b't', b'y', b'p', b'e', 0x02, 0x64, b'd', b'a', b't', b'a', 0x81, 0x63,
+            b'f', b'o', b'o', 0x63, b'n', b's', b'p', 0x61, b'/',
+        ];
+
+        assert_eq!(CborParser::encode_packet(&message).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_connect_to_default_namespace() {
+        let message = SocketIoMessage::Connect {
+            namespace: "/".into(),
+            sid: None,
+            auth: None,
+        };
+
+        match round_trip(&message) {
+            SocketIoMessage::Connect { namespace, sid, .. } => {
+                assert_eq!(namespace.as_str(), "/");
+                assert!(sid.is_none());
+            }
+            _ => panic!("Invalid message"),
+        }
+    }
+
+    #[test]
+    fn test_connect_to_a_custom_namespace() {
+        let message = SocketIoMessage::Connect {
+            namespace: "/admin".into(),
+            sid: Some("oSO0OpakMV_3jnilAAAA".into()),
+            auth: None,
+        };
+
+        match round_trip(&message) {
+            SocketIoMessage::Connect { namespace, sid, .. } => {
+                assert_eq!(namespace.as_str(), "/admin");
+                assert_eq!(sid.unwrap().as_str(), "oSO0OpakMV_3jnilAAAA");
+            }
+            _ => panic!("Invalid message"),
+        }
+    }
+
+    #[test]
+    fn test_connect_error_default_namespace() {
+        let message = SocketIoMessage::ConnectError {
+            namespace: "/".into(),
+            message: "Not authorized".into(),
+        };
+
+        match round_trip(&message) {
+            SocketIoMessage::ConnectError { namespace, message } => {
+                assert_eq!(namespace.as_str(), "/");
+                assert_eq!(message.as_str(), "Not authorized");
+            }
+            _ => panic!("Invalid message"),
+        }
+    }
+
+    #[test]
+    fn test_sending_event_to_default_namespace() {
+        let message = SocketIoMessage::Event {
+            namespace: "/".into(),
+            event_name: "foo".into(),
+            data: "".into(),
+            rest_args: Vec::new(),
+            ack: None,
+        };
+
+        match round_trip(&message) {
+            SocketIoMessage::Event {
+                namespace,
+                event_name,
+                data,
+                ack,
+                ..
+            } => {
+                assert_eq!(namespace.as_str(), "/");
+                assert_eq!(event_name.as_str(), "foo");
+                assert_eq!(data.as_str(), "");
+                assert!(ack.is_none());
+            }
+            _ => panic!("Invalid message"),
+        }
+    }
+
+    #[test]
+    fn test_sending_event_to_custom_namespace_with_params() {
+        let message = SocketIoMessage::Event {
+            namespace: "/admin".into(),
+            event_name: "foo".into(),
+            data: "{\"type\":\"AccountStatus\",\"accountId\":\"L#711000\"}".into(),
+            rest_args: Vec::new(),
+            ack: None,
+        };
+
+        match round_trip(&message) {
+            SocketIoMessage::Event {
+                namespace,
+                event_name,
+                data,
+                ack,
+                ..
+            } => {
+                assert_eq!(namespace.as_str(), "/admin");
+                assert_eq!(event_name.as_str(), "foo");
+                assert_eq!(
+                    data.as_str(),
+                    "{\"type\":\"AccountStatus\",\"accountId\":\"L#711000\"}"
+                );
+                assert!(ack.is_none());
+            }
+            _ => panic!("Invalid message"),
+        }
+    }
+
+    #[test]
+    fn test_sending_event_to_default_namespace_with_ack() {
+        let message = SocketIoMessage::Event {
+            namespace: "/".into(),
+            event_name: "foo".into(),
+            data: "".into(),
+            rest_args: Vec::new(),
+            ack: Some(12),
+        };
+
+        match round_trip(&message) {
+            SocketIoMessage::Event {
+                event_name, ack, ..
+            } => {
+                assert_eq!(event_name.as_str(), "foo");
+                assert_eq!(ack, Some(12));
+            }
+            _ => panic!("Invalid message"),
+        }
+    }
+
+    #[test]
+    fn test_ack_with_custom_namespace() {
+        let message = SocketIoMessage::Ack {
+            namespace: "/admin".into(),
+            event_name: "bar".into(),
+            data: "".into(),
+            rest_args: Vec::new(),
+            ack: 13,
+        };
+
+        match round_trip(&message) {
+            SocketIoMessage::Ack {
+                namespace,
+                event_name,
+                data,
+                ack,
+                ..
+            } => {
+                assert_eq!(namespace.as_str(), "/admin");
+                assert_eq!(event_name.as_str(), "bar");
+                assert_eq!(data.as_str(), "");
+                assert_eq!(ack, 13);
+            }
+            _ => panic!("Invalid message"),
+        }
+    }
+
+    #[test]
+    fn test_disconnect_from_admin_namespace() {
+        let message = SocketIoMessage::Disconnect {
+            namespace: "/admin".into(),
+        };
+
+        match round_trip(&message) {
+            SocketIoMessage::Disconnect { namespace } => {
+                assert_eq!(namespace.as_str(), "/admin");
+            }
+            _ => panic!("Invalid message"),
+        }
+    }
+
+    #[test]
+    fn test_binary_event_with_custom_namespace() {
+        let message = SocketIoMessage::BinaryEvent {
+            namespace: "/admin".into(),
+            event_name: "file".into(),
+            data: r#"{"_placeholder":true,"num":0}"#.into(),
+            rest_args: Vec::new(),
+            ack: Some(5),
+            attachments: vec![vec![1, 2, 3]],
+        };
+
+        match round_trip(&message) {
+            SocketIoMessage::BinaryEvent {
+                namespace,
+                event_name,
+                data,
+                ack,
+                attachments,
+                ..
+            } => {
+                assert_eq!(namespace.as_str(), "/admin");
+                assert_eq!(event_name.as_str(), "file");
+                assert_eq!(data.as_str(), r#"{"_placeholder":true,"num":0}"#);
+                assert_eq!(ack, Some(5));
+                assert_eq!(attachments, vec![vec![1, 2, 3]]);
+            }
+            _ => panic!("Invalid message"),
+        }
+    }
+
+    #[test]
+    fn test_binary_ack_to_default_namespace() {
+        let message = SocketIoMessage::BinaryAck {
+            namespace: "/".into(),
+            event_name: "file".into(),
+            data: r#"{"_placeholder":true,"num":0}"#.into(),
+            rest_args: Vec::new(),
+            ack: 7,
+            attachments: vec![vec![1, 2, 3]],
+        };
+
+        match round_trip(&message) {
+            SocketIoMessage::BinaryAck {
+                namespace,
+                ack,
+                attachments,
+                ..
+            } => {
+                assert_eq!(namespace.as_str(), "/");
+                assert_eq!(ack, 7);
+                assert_eq!(attachments, vec![vec![1, 2, 3]]);
+            }
+            _ => panic!("Invalid message"),
+        }
+    }
+
+    #[test]
+    fn test_invalid_packet() {
+        let mut decoder = CborParser.create_decoder(SocketIoDecodeLimits::default());
+
+        assert!(decoder.decode_text(r#"2["foo"]"#).is_err());
+        assert!(decoder.decode_binary(vec![0x82, 0x01, 0x02]).is_err());
+    }
+}
@@ -0,0 +1,456 @@
+use crate::{EngineIoDecodeError, SocketIoMessage, SocketIoSettings};
+
+/// Same as Socket.IO `maxHttpBufferSize` default
+pub const DEFAULT_MAX_PAYLOAD_SIZE: usize = 1_000_000;
+pub const DEFAULT_MAX_BINARY_ATTACHMENTS: usize = 10;
+pub const DEFAULT_MAX_JSON_DEPTH: usize = 32;
+pub const DEFAULT_MAX_EVENT_ARGUMENTS: usize = 64;
+pub const DEFAULT_MAX_EVENT_NAME_LENGTH: usize = 256;
+pub const DEFAULT_MAX_NAMESPACE_LENGTH: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SocketIoDecodeError {
+    PayloadTooLarge { size: usize, max_size: usize },
+    TooManyBinaryAttachments { count: usize, max_count: usize },
+    BinaryAttachmentTooLarge { size: usize, max_size: usize },
+    JsonTooDeep { max_depth: usize },
+    TooManyArguments { max_count: usize },
+    EventNameTooLong { size: usize, max_size: usize },
+    NamespaceTooLong { size: usize, max_size: usize },
+    InvalidPacket(String),
+}
+
+impl From<EngineIoDecodeError> for SocketIoDecodeError {
+    fn from(value: EngineIoDecodeError) -> Self {
+        Self::InvalidPacket(format!("{:?}", value))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SocketIoDecodeLimits {
+    /// Max size of a text frame or a polling request body
+    pub max_payload_size: usize,
+    pub max_binary_attachments: usize,
+    pub max_binary_attachment_size: usize,
+    pub max_json_depth: usize,
+    /// Max arguments of Event/Ack not counting event name
+    pub max_event_arguments: usize,
+    pub max_event_name_length: usize,
+    pub max_namespace_length: usize,
+}
+
+impl Default for SocketIoDecodeLimits {
+    fn default() -> Self {
+        Self {
+            max_payload_size: DEFAULT_MAX_PAYLOAD_SIZE,
+            max_binary_attachments: DEFAULT_MAX_BINARY_ATTACHMENTS,
+            max_binary_attachment_size: DEFAULT_MAX_PAYLOAD_SIZE,
+            max_json_depth: DEFAULT_MAX_JSON_DEPTH,
+            max_event_arguments: DEFAULT_MAX_EVENT_ARGUMENTS,
+            max_event_name_length: DEFAULT_MAX_EVENT_NAME_LENGTH,
+            max_namespace_length: DEFAULT_MAX_NAMESPACE_LENGTH,
+        }
+    }
+}
+
+impl SocketIoDecodeLimits {
+    pub fn from_settings(settings: &SocketIoSettings) -> Self {
+        Self {
+            max_payload_size: settings.max_payload_size,
+            max_binary_attachments: DEFAULT_MAX_BINARY_ATTACHMENTS,
+            max_binary_attachment_size: settings.max_payload_size,
+            max_json_depth: DEFAULT_MAX_JSON_DEPTH,
+            max_event_arguments: DEFAULT_MAX_EVENT_ARGUMENTS,
+            max_event_name_length: DEFAULT_MAX_EVENT_NAME_LENGTH,
+            max_namespace_length: DEFAULT_MAX_NAMESPACE_LENGTH,
+        }
+    }
+
+    pub fn check_text_frame(&self, src: &str) -> Result<(), SocketIoDecodeError> {
+        check_size(src.len(), self.max_payload_size)
+    }
+
+    pub fn check_polling_body(&self, body: &[u8]) -> Result<(), SocketIoDecodeError> {
+        check_size(body.len(), self.max_payload_size)
+    }
+
+    pub fn check_binary_attachments_count(&self, count: usize) -> Result<(), SocketIoDecodeError> {
+        if count > self.max_binary_attachments {
+            return Err(SocketIoDecodeError::TooManyBinaryAttachments {
+                count,
+                max_count: self.max_binary_attachments,
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn check_binary_frame(&self, data: &[u8]) -> Result<(), SocketIoDecodeError> {
+        if data.len() > self.max_binary_attachment_size {
+            return Err(SocketIoDecodeError::BinaryAttachmentTooLarge {
+                size: data.len(),
+                max_size: self.max_binary_attachment_size,
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn check_namespace(&self, namespace: &str) -> Result<(), SocketIoDecodeError> {
+        if namespace.len() > self.max_namespace_length {
+            return Err(SocketIoDecodeError::NamespaceTooLong {
+                size: namespace.len(),
+                max_size: self.max_namespace_length,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Single pass over `["event_name", arg1, arg2...]` without recursion. Stops as soon as any limit is exceeded
+    pub fn check_event_data(&self, data: &str) -> Result<(), SocketIoDecodeError> {
+        let mut depth = 0;
+        let mut in_string = false;
+        let mut escape = false;
+        let mut expect_item = false;
+        let mut items = 0;
+        let mut event_name_start = None;
+
+        for (index, b) in data.bytes().enumerate() {
+            if in_string {
+                if escape {
+                    escape = false;
+                } else if b == b'\\' {
+                    escape = true;
+                } else if b == b'"' {
+                    in_string = false;
+
+                    if let Some(start) = event_name_start.take() {
+                        self.check_event_name_length(index - start)?;
+                    }
+                }
+
+                if let Some(start) = event_name_start {
+                    self.check_event_name_length(index - start)?;
+                }
+
+                continue;
+            }
+
+            if b.is_ascii_whitespace() {
+                continue;
+            }
+
+            if depth == 1 && expect_item && b != b']' && b != b',' {
+                expect_item = false;
+                items += 1;
+
+                if items > self.max_event_arguments + 1 {
+                    return Err(SocketIoDecodeError::TooManyArguments {
+                        max_count: self.max_event_arguments,
+                    });
+                }
+
+                if items == 1 && b == b'"' {
+                    event_name_start = Some(index + 1);
+                }
+            }
+
+            match b {
+                b'"' => in_string = true,
+                b'[' | b'{' => {
+                    depth += 1;
+
+                    if depth > self.max_json_depth {
+                        return Err(SocketIoDecodeError::JsonTooDeep {
+                            max_depth: self.max_json_depth,
+                        });
+                    }
+
+                    if depth == 1 {
+                        expect_item = true;
+                    }
+                }
+                b']' | b'}' => {
+                    if depth == 0 {
+                        return Err(SocketIoDecodeError::InvalidPacket(data.to_string()));
+                    }
+
+                    depth -= 1;
+                }
+                b',' if depth == 1 => expect_item = true,
+                _ => {}
+            }
+        }
+
+        if in_string || depth != 0 {
+            return Err(SocketIoDecodeError::InvalidPacket(data.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Used by binary parsers after the packet is decoded. Event data is checked the same way as JSON of text frames
+    pub fn check_message(&self, message: &SocketIoMessage) -> Result<(), SocketIoDecodeError> {
+        self.check_namespace(message.get_namespace())?;
+
+        if let Some(attachments) = message.get_attachments() {
+            self.check_binary_attachments_count(attachments.len())?;
+        }
+
+        let (event_name, data) = match message {
+            SocketIoMessage::Event { event_name, .. }
+            | SocketIoMessage::Ack { event_name, .. }
+            | SocketIoMessage::BinaryEvent { event_name, .. }
+            | SocketIoMessage::BinaryAck { event_name, .. } => (
+                event_name.as_str(),
+                message.get_args_data().unwrap_or_default(),
+            ),
+            _ => return Ok(()),
+        };
+
+        let mut json = String::from("[");
+
+        if !event_name.is_empty() {
+            json.push_str(&serde_json::to_string(event_name).unwrap());
+
+            if !data.is_empty() {
+                json.push(',');
+            }
+        }
+
+        json.push_str(&data);
+        json.push(']');
+
+        self.check_event_data(&json)
+    }
+
+    fn check_event_name_length(&self, size: usize) -> Result<(), SocketIoDecodeError> {
+        if size > self.max_event_name_length {
+            return Err(SocketIoDecodeError::EventNameTooLong {
+                size,
+                max_size: self.max_event_name_length,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+fn check_size(size: usize, max_size: usize) -> Result<(), SocketIoDecodeError> {
+    if size > max_size {
+        return Err(SocketIoDecodeError::PayloadTooLarge { size, max_size });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SocketIoContract;
+
+    fn create_limits() -> SocketIoDecodeLimits {
+        SocketIoDecodeLimits {
+            max_payload_size: 16,
+            max_binary_attachments: 2,
+            max_binary_attachment_size: 4,
+            max_json_depth: DEFAULT_MAX_JSON_DEPTH,
+            max_event_arguments: DEFAULT_MAX_EVENT_ARGUMENTS,
+            max_event_name_length: DEFAULT_MAX_EVENT_NAME_LENGTH,
+            max_namespace_length: DEFAULT_MAX_NAMESPACE_LENGTH,
+        }
+    }
+
+    fn create_json_limits() -> SocketIoDecodeLimits {
+        SocketIoDecodeLimits {
+            max_payload_size: 1024,
+            max_binary_attachments: 2,
+            max_binary_attachment_size: 4,
+            max_json_depth: 3,
+            max_event_arguments: 2,
+            max_event_name_length: 5,
+            max_namespace_length: 6,
+        }
+    }
+
+    #[test]
+    fn test_text_frame_within_limit() {
+        let result = SocketIoContract::deserialize_with_limits(r#"42["foo"]"#, &create_limits());
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_oversized_text_frame() {
+        let result = SocketIoContract::deserialize_with_limits(
+            r#"42["foo","very long payload"]"#,
+            &create_limits(),
+        );
+
+        assert_eq!(
+            result.err().unwrap(),
+            SocketIoDecodeError::PayloadTooLarge {
+                size: 29,
+                max_size: 16
+            }
+        );
+    }
+
+    #[test]
+    fn test_too_many_binary_attachments() {
+        let result = SocketIoContract::deserialize_with_limits(r#"4512-["foo"]"#, &create_limits());
+
+        assert_eq!(
+            result.err().unwrap(),
+            SocketIoDecodeError::TooManyBinaryAttachments {
+                count: 12,
+                max_count: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_binary_frame_size() {
+        let limits = create_limits();
+
+        assert!(limits.check_binary_frame(&[1, 2, 3, 4]).is_ok());
+        assert_eq!(
+            limits.check_binary_frame(&[1, 2, 3, 4, 5]).unwrap_err(),
+            SocketIoDecodeError::BinaryAttachmentTooLarge {
+                size: 5,
+                max_size: 4
+            }
+        );
+    }
+
+    #[test]
+    fn test_event_within_json_limits() {
+        let result = SocketIoMessage::deserialize_with_limits(
+            r#"2/admin,["foo",{"a":[1,"]]]"]},"b"]"#,
+            &create_json_limits(),
+        )
+        .unwrap();
+
+        match result {
+            SocketIoMessage::Event {
+                namespace,
+                event_name,
+                ..
+            } => {
+                assert_eq!(namespace.as_str(), "/admin");
+                assert_eq!(event_name.as_str(), "foo");
+            }
+            _ => panic!("Invalid message"),
+        }
+    }
+
+    #[test]
+    fn test_json_too_deep() {
+        let result = SocketIoMessage::deserialize_with_limits(
+            r#"2["foo",{"a":[[1]]}]"#,
+            &create_json_limits(),
+        );
+
+        assert_eq!(
+            result.err().unwrap(),
+            SocketIoDecodeError::JsonTooDeep { max_depth: 3 }
+        );
+    }
+
+    #[test]
+    fn test_too_many_arguments() {
+        let result =
+            SocketIoMessage::deserialize_with_limits(r#"2["foo",1,2,3]"#, &create_json_limits());
+
+        assert_eq!(
+            result.err().unwrap(),
+            SocketIoDecodeError::TooManyArguments { max_count: 2 }
+        );
+    }
+
+    #[test]
+    fn test_event_name_too_long() {
+        let result =
+            SocketIoMessage::deserialize_with_limits(r#"2["foobar"]"#, &create_json_limits());
+
+        assert_eq!(
+            result.err().unwrap(),
+            SocketIoDecodeError::EventNameTooLong {
+                size: 6,
+                max_size: 5
+            }
+        );
+    }
+
+    #[test]
+    fn test_namespace_too_long() {
+        let result = SocketIoContract::deserialize_with_limits(
+            r#"42/administrator,["foo"]"#,
+            &create_json_limits(),
+        );
+
+        assert_eq!(
+            result.err().unwrap(),
+            SocketIoDecodeError::NamespaceTooLong {
+                size: 14,
+                max_size: 6
+            }
+        );
+    }
+
+    #[test]
+    fn test_malformed_event_data() {
+        let result =
+            SocketIoMessage::deserialize_with_limits(r#"2["foo",{"a":1]"#, &create_json_limits());
+        assert!(matches!(result, Err(SocketIoDecodeError::InvalidPacket(_))));
+
+        let result = SocketIoMessage::deserialize_with_limits(r#"2[1,2]"#, &create_json_limits());
+        assert!(matches!(result, Err(SocketIoDecodeError::InvalidPacket(_))));
+    }
+
+    #[test]
+    fn test_decoded_message_limits() {
+        let limits = create_json_limits();
+
+        let event = |namespace: &'static str, data: &'static str| SocketIoMessage::Event {
+            namespace: namespace.into(),
+            event_name: "foo".into(),
+            data: data.into(),
+            rest_args: Vec::new(),
+            ack: None,
+        };
+
+        assert!(limits.check_message(&event("/", r#"{"a":[1]},2"#)).is_ok());
+        assert_eq!(
+            limits.check_message(&event("/", r#"{"a":[[1]]}"#)),
+            Err(SocketIoDecodeError::JsonTooDeep { max_depth: 3 })
+        );
+        assert_eq!(
+            limits.check_message(&event("/administrator", "")),
+            Err(SocketIoDecodeError::NamespaceTooLong {
+                size: 14,
+                max_size: 6
+            })
+        );
+    }
+
+    #[test]
+    fn test_malformed_engine_packets() {
+        let limits = SocketIoDecodeLimits::default();
+
+        for src in ["", "0{", "0", "9", "x"] {
+            let result = SocketIoContract::deserialize_with_limits(src, &limits);
+            assert!(
+                matches!(result, Err(SocketIoDecodeError::InvalidPacket(_))),
+                "{:?}",
+                src
+            );
+        }
+
+        let result = SocketIoContract::deserialize_with_limits("3probe", &limits);
+        assert!(matches!(
+            result,
+            Ok(SocketIoContract::Pong { payload: Some(_) })
+        ));
+    }
+}
@@ -0,0 +1,31 @@
+use crate::SocketIoPayload;
+
+/// Per emit flags which are passed to the transport together with the payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmitOptions {
+    /// Payload may be dropped if the connection is not ready to write
+    pub volatile: bool,
+    /// Per-message deflate hint. Tungstenite has no permessage-deflate, so WebSocket frames are sent uncompressed
+    pub compress: bool,
+}
+
+impl Default for EmitOptions {
+    fn default() -> Self {
+        Self {
+            volatile: false,
+            compress: true,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct OutgoingPayload {
+    pub payload: SocketIoPayload,
+    pub options: EmitOptions,
+}
+
+impl OutgoingPayload {
+    pub fn new(payload: SocketIoPayload, options: EmitOptions) -> Self {
+        Self { payload, options }
+    }
+}
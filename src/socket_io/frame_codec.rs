@@ -0,0 +1,125 @@
+use bytes::BytesMut;
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::{
+    FrameDecoder, SocketIoContract, SocketIoDecodeError, SocketIoPacketDecoder, SocketIoPayload,
+};
+
+#[derive(Debug)]
+pub enum FrameCodecError {
+    Io(std::io::Error),
+    Decode(SocketIoDecodeError),
+}
+
+impl From<std::io::Error> for FrameCodecError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<SocketIoDecodeError> for FrameCodecError {
+    fn from(value: SocketIoDecodeError) -> Self {
+        Self::Decode(value)
+    }
+}
+
+impl<D: SocketIoPacketDecoder> Decoder for FrameDecoder<D> {
+    type Item = SocketIoContract;
+    type Error = FrameCodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if !src.is_empty() {
+            self.push(&src.split());
+        }
+
+        Ok(self.next_contract()?)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if let Some(contract) = self.decode(src)? {
+            return Ok(Some(contract));
+        }
+
+        if self.buffered_len() > 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "Stream is closed in the middle of a frame",
+            )
+            .into());
+        }
+
+        Ok(None)
+    }
+}
+
+impl<D: SocketIoPacketDecoder> Encoder<SocketIoPayload> for FrameDecoder<D> {
+    type Error = FrameCodecError;
+
+    fn encode(&mut self, item: SocketIoPayload, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let mut out = Vec::new();
+        self.get_format().encode_payload(&item, &mut out);
+        dst.extend_from_slice(&out);
+        Ok(())
+    }
+}
+
+impl<D: SocketIoPacketDecoder> Encoder<SocketIoContract> for FrameDecoder<D> {
+    type Error = FrameCodecError;
+
+    fn encode(&mut self, item: SocketIoContract, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.encode(item.serialize(), dst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FrameFormat, SocketIoMessage};
+
+    #[test]
+    fn test_codec_round_trip() {
+        let mut codec = FrameDecoder::new(FrameFormat::LengthPrefixed);
+        let mut buffer = BytesMut::new();
+
+        codec
+            .encode(SocketIoContract::Ping { payload: None }, &mut buffer)
+            .unwrap();
+        codec
+            .encode(
+                SocketIoContract::Message(SocketIoMessage::Event {
+                    namespace: "/".into(),
+                    event_name: "foo".into(),
+                    data: "1".into(),
+                    rest_args: Vec::new(),
+                    ack: None,
+                }),
+                &mut buffer,
+            )
+            .unwrap();
+
+        let mut tail = buffer.split_off(3);
+
+        assert!(codec.decode(&mut buffer).unwrap().is_none());
+
+        assert!(matches!(
+            codec.decode(&mut tail).unwrap(),
+            Some(SocketIoContract::Ping { payload: None })
+        ));
+        assert!(matches!(
+            codec.decode_eof(&mut tail).unwrap(),
+            Some(SocketIoContract::Message(SocketIoMessage::Event { .. }))
+        ));
+        assert!(codec.decode_eof(&mut tail).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_eof_in_the_middle_of_frame() {
+        let mut codec = FrameDecoder::new(FrameFormat::RecordSeparator);
+        let mut buffer = BytesMut::from(&b"42[\"fo"[..]);
+
+        assert!(matches!(
+            codec.decode_eof(&mut buffer),
+            Err(FrameCodecError::Io(_))
+        ));
+    }
+}
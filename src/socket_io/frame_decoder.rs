@@ -0,0 +1,335 @@
+use base64::Engine;
+
+use crate::{
+    DefaultPacketDecoder, DefaultParser, SocketIoContract, SocketIoContractDecoder,
+    SocketIoDecodeError, SocketIoDecodeLimits, SocketIoPacketDecoder, SocketIoParser,
+    SocketIoPayload,
+};
+
+const RECORD_SEPARATOR: u8 = 0x1e;
+const LENGTH_PREFIX_HEADER_SIZE: usize = 5;
+const LENGTH_PREFIX_TEXT: u8 = 0;
+const LENGTH_PREFIX_BINARY: u8 = 1;
+
+/// How Engine.IO packets are delimited inside of a byte stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameFormat {
+    /// Each packet is followed by `\x1e`. Binary packets are sent as `b<base64>` the same way as in polling payloads
+    RecordSeparator,
+    /// Each packet is prefixed with a kind byte (`0` - text, `1` - binary) and u32 big endian length
+    LengthPrefixed,
+}
+
+impl FrameFormat {
+    pub fn encode_payload(&self, payload: &SocketIoPayload, out: &mut Vec<u8>) {
+        if !payload.text_frame.is_empty() {
+            self.encode_text_frame(&payload.text_frame, out);
+        }
+
+        for binary_frame in &payload.binary_frames {
+            self.encode_binary_frame(binary_frame, out);
+        }
+    }
+
+    pub fn encode_text_frame(&self, frame: &str, out: &mut Vec<u8>) {
+        match self {
+            FrameFormat::RecordSeparator => {
+                out.extend_from_slice(frame.as_bytes());
+                out.push(RECORD_SEPARATOR);
+            }
+            FrameFormat::LengthPrefixed => {
+                write_length_prefix(LENGTH_PREFIX_TEXT, frame.len(), out);
+                out.extend_from_slice(frame.as_bytes());
+            }
+        }
+    }
+
+    pub fn encode_binary_frame(&self, frame: &[u8], out: &mut Vec<u8>) {
+        match self {
+            FrameFormat::RecordSeparator => {
+                let mut encoded = String::from("b");
+                base64::engine::general_purpose::STANDARD.encode_string(frame, &mut encoded);
+                out.extend_from_slice(encoded.as_bytes());
+                out.push(RECORD_SEPARATOR);
+            }
+            FrameFormat::LengthPrefixed => {
+                write_length_prefix(LENGTH_PREFIX_BINARY, frame.len(), out);
+                out.extend_from_slice(frame);
+            }
+        }
+    }
+}
+
+enum Frame {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+/// Accepts byte chunks as they come from a stream and returns contracts as soon as they are complete
+pub struct FrameDecoder<D: SocketIoPacketDecoder = DefaultPacketDecoder> {
+    format: FrameFormat,
+    limits: SocketIoDecodeLimits,
+    decoder: SocketIoContractDecoder<D>,
+    buffer: Vec<u8>,
+    scanned: usize,
+}
+
+impl FrameDecoder<DefaultPacketDecoder> {
+    pub fn new(format: FrameFormat) -> Self {
+        Self::new_with_parser(&DefaultParser, format, SocketIoDecodeLimits::default())
+    }
+}
+
+impl<D: SocketIoPacketDecoder> FrameDecoder<D> {
+    pub fn new_with_parser<P: SocketIoParser<Decoder = D>>(
+        parser: &P,
+        format: FrameFormat,
+        limits: SocketIoDecodeLimits,
+    ) -> Self {
+        Self {
+            format,
+            decoder: SocketIoContractDecoder::new(parser, limits.clone()),
+            limits,
+            buffer: Vec::new(),
+            scanned: 0,
+        }
+    }
+
+    /// Engine.IO messages are returned as [`SocketIoContract::EngineMessage`] without Socket.IO parsing
+    pub fn with_raw_mode(mut self) -> Self {
+        self.decoder.set_raw_mode(true);
+        self
+    }
+
+    pub fn get_format(&self) -> FrameFormat {
+        self.format
+    }
+
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// Bytes of a frame which is not complete yet
+    pub fn buffered_len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn next_contract(&mut self) -> Result<Option<SocketIoContract>, SocketIoDecodeError> {
+        while let Some(frame) = self.next_frame()? {
+            let contract = match frame {
+                Frame::Text(frame) => self.decoder.decode_text_frame(&frame)?,
+                Frame::Binary(frame) => self.decoder.decode_binary_frame(frame)?,
+            };
+
+            if contract.is_some() {
+                return Ok(contract);
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn next_frame(&mut self) -> Result<Option<Frame>, SocketIoDecodeError> {
+        match self.format {
+            FrameFormat::RecordSeparator => self.next_separated_frame(),
+            FrameFormat::LengthPrefixed => self.next_length_prefixed_frame(),
+        }
+    }
+
+    fn next_separated_frame(&mut self) -> Result<Option<Frame>, SocketIoDecodeError> {
+        let position = self.buffer[self.scanned..]
+            .iter()
+            .position(|b| *b == RECORD_SEPARATOR);
+
+        let size = match position {
+            Some(position) => self.scanned + position,
+            None => {
+                self.scanned = self.buffer.len();
+                check_frame_size(self.scanned, self.get_max_separated_frame_size())?;
+                return Ok(None);
+            }
+        };
+
+        check_frame_size(size, self.get_max_separated_frame_size())?;
+
+        let mut frame: Vec<u8> = self.buffer.drain(..size + 1).collect();
+        frame.pop();
+        self.scanned = 0;
+
+        if frame.first() == Some(&b'b') {
+            let data = base64::engine::general_purpose::STANDARD
+                .decode(&frame[1..])
+                .map_err(|err| {
+                    SocketIoDecodeError::InvalidPacket(format!("Invalid base64: {}", err))
+                })?;
+
+            return Ok(Some(Frame::Binary(data)));
+        }
+
+        Ok(Some(Frame::Text(into_text(frame)?)))
+    }
+
+    fn next_length_prefixed_frame(&mut self) -> Result<Option<Frame>, SocketIoDecodeError> {
+        if self.buffer.len() < LENGTH_PREFIX_HEADER_SIZE {
+            return Ok(None);
+        }
+
+        let kind = self.buffer[0];
+        let size = u32::from_be_bytes(self.buffer[1..5].try_into().unwrap()) as usize;
+
+        match kind {
+            LENGTH_PREFIX_TEXT => check_frame_size(size, self.limits.max_payload_size)?,
+            LENGTH_PREFIX_BINARY => check_frame_size(size, self.limits.max_binary_attachment_size)?,
+            _ => {
+                return Err(SocketIoDecodeError::InvalidPacket(format!(
+                    "Unknown frame kind {}",
+                    kind
+                )))
+            }
+        }
+
+        if self.buffer.len() < LENGTH_PREFIX_HEADER_SIZE + size {
+            return Ok(None);
+        }
+
+        let frame: Vec<u8> = self
+            .buffer
+            .drain(..LENGTH_PREFIX_HEADER_SIZE + size)
+            .skip(LENGTH_PREFIX_HEADER_SIZE)
+            .collect();
+
+        if kind == LENGTH_PREFIX_BINARY {
+            return Ok(Some(Frame::Binary(frame)));
+        }
+
+        Ok(Some(Frame::Text(into_text(frame)?)))
+    }
+
+    /// Binary frames are base64 encoded so they are allowed to be bigger than the attachment limit
+    fn get_max_separated_frame_size(&self) -> usize {
+        let base64_size = self.limits.max_binary_attachment_size.div_ceil(3) * 4 + 1;
+        self.limits.max_payload_size.max(base64_size)
+    }
+}
+
+fn write_length_prefix(kind: u8, size: usize, out: &mut Vec<u8>) {
+    out.push(kind);
+    out.extend_from_slice(&(size as u32).to_be_bytes());
+}
+
+fn check_frame_size(size: usize, max_size: usize) -> Result<(), SocketIoDecodeError> {
+    if size > max_size {
+        return Err(SocketIoDecodeError::PayloadTooLarge { size, max_size });
+    }
+
+    Ok(())
+}
+
+fn into_text(frame: Vec<u8>) -> Result<String, SocketIoDecodeError> {
+    String::from_utf8(frame)
+        .map_err(|err| SocketIoDecodeError::InvalidPacket(format!("Invalid utf8: {}", err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SocketIoMessage;
+
+    fn create_binary_payload() -> SocketIoPayload {
+        let message = SocketIoMessage::BinaryEvent {
+            namespace: "/".into(),
+            event_name: "file".into(),
+            data: r#"{"_placeholder":true,"num":0}"#.into(),
+            rest_args: Vec::new(),
+            ack: None,
+            attachments: vec![vec![1, 2, 3]],
+        };
+
+        DefaultParser.encode_to_engine_payload(&message).unwrap()
+    }
+
+    #[test]
+    fn test_record_separated_chunks() {
+        let mut stream = Vec::new();
+        let format = FrameFormat::RecordSeparator;
+        format.encode_payload(
+            &SocketIoContract::Ping { payload: None }.serialize(),
+            &mut stream,
+        );
+        format.encode_payload(&create_binary_payload(), &mut stream);
+
+        let mut decoder = FrameDecoder::new(format);
+        let mut contracts = Vec::new();
+
+        for b in stream {
+            decoder.push(&[b]);
+
+            while let Some(contract) = decoder.next_contract().unwrap() {
+                contracts.push(contract);
+            }
+        }
+
+        assert_eq!(contracts.len(), 2);
+        assert!(matches!(
+            contracts[0],
+            SocketIoContract::Ping { payload: None }
+        ));
+
+        match &contracts[1] {
+            SocketIoContract::Message(SocketIoMessage::BinaryEvent { attachments, .. }) => {
+                assert_eq!(attachments, &vec![vec![1, 2, 3]])
+            }
+            _ => panic!("BinaryEvent is expected"),
+        }
+
+        assert_eq!(decoder.buffered_len(), 0);
+    }
+
+    #[test]
+    fn test_length_prefixed_chunks() {
+        let mut stream = Vec::new();
+        let format = FrameFormat::LengthPrefixed;
+        format.encode_payload(&create_binary_payload(), &mut stream);
+        format.encode_text_frame("3", &mut stream);
+
+        let mut decoder = FrameDecoder::new(format);
+
+        decoder.push(&stream[..7]);
+        assert!(decoder.next_contract().unwrap().is_none());
+
+        decoder.push(&stream[7..]);
+
+        assert!(matches!(
+            decoder.next_contract().unwrap(),
+            Some(SocketIoContract::Message(
+                SocketIoMessage::BinaryEvent { .. }
+            ))
+        ));
+        assert!(matches!(
+            decoder.next_contract().unwrap(),
+            Some(SocketIoContract::Pong { payload: None })
+        ));
+        assert!(decoder.next_contract().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_oversized_frame_is_rejected_before_it_is_complete() {
+        let limits = SocketIoDecodeLimits {
+            max_payload_size: 8,
+            max_binary_attachment_size: 4,
+            ..Default::default()
+        };
+
+        let mut decoder =
+            FrameDecoder::new_with_parser(&DefaultParser, FrameFormat::LengthPrefixed, limits);
+        decoder.push(&[LENGTH_PREFIX_TEXT, 0, 0, 0, 9]);
+
+        assert_eq!(
+            decoder.next_contract().err().unwrap(),
+            SocketIoDecodeError::PayloadTooLarge {
+                size: 9,
+                max_size: 8
+            }
+        );
+    }
+}
@@ -0,0 +1,42 @@
+mod socket_io_contract;
+pub use socket_io_contract::*;
+mod socket_io_message;
+pub use socket_io_message::*;
+mod socket_io_data;
+pub use socket_io_data::*;
+mod payload_deserializer;
+pub use payload_deserializer::*;
+mod payload_serializer;
+pub use payload_serializer::*;
+mod emit_options;
+pub use emit_options::*;
+mod decode_limits;
+pub use decode_limits::*;
+mod socket_io_parser;
+pub use socket_io_parser::*;
+mod frame_decoder;
+pub use frame_decoder::*;
+#[cfg(feature = "tokio-ws")]
+mod web_socket_transport;
+#[cfg(feature = "tokio-ws")]
+pub use web_socket_transport::*;
+#[cfg(feature = "polling")]
+mod polling_transport;
+#[cfg(feature = "polling")]
+pub use polling_transport::*;
+#[cfg(any(feature = "msgpack", feature = "cbor"))]
+mod value_tree_packet;
+#[cfg(any(feature = "msgpack", feature = "cbor"))]
+pub(crate) use value_tree_packet::*;
+#[cfg(feature = "msgpack")]
+mod msgpack_parser;
+#[cfg(feature = "msgpack")]
+pub use msgpack_parser::*;
+#[cfg(feature = "cbor")]
+mod cbor_parser;
+#[cfg(feature = "cbor")]
+pub use cbor_parser::*;
+#[cfg(feature = "codec")]
+mod frame_codec;
+#[cfg(feature = "codec")]
+pub use frame_codec::*;
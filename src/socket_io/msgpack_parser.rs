@@ -0,0 +1,516 @@
+use rmpv::Value;
+
+use crate::{
+    decode_value_packet, encode_value_packet, PacketValue, SocketIoDecodeError,
+    SocketIoDecodeLimits, SocketIoEncodeError, SocketIoMessage, SocketIoPacketDecoder,
+    SocketIoParser, SocketIoPayload, PACKET_TYPE_ACK, PACKET_TYPE_EVENT,
+};
+
+/// Packet encoding compatible with `socket.io-msgpack-parser`.
+/// Each packet is a msgpack map `{type, data, nsp, id}` sent as a single binary frame, binary attachments are inline.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MsgPackParser;
+
+impl SocketIoParser for MsgPackParser {
+    type Decoder = MsgPackPacketDecoder;
+
+    fn encode(&self, message: &SocketIoMessage) -> Result<SocketIoPayload, SocketIoEncodeError> {
+        let mut result = SocketIoPayload::new();
+        result.binary_frames.push(Self::encode_packet(message)?);
+        Ok(result)
+    }
+
+    fn create_decoder(&self, limits: SocketIoDecodeLimits) -> Self::Decoder {
+        MsgPackPacketDecoder { limits }
+    }
+}
+
+pub struct MsgPackPacketDecoder {
+    limits: SocketIoDecodeLimits,
+}
+
+impl SocketIoPacketDecoder for MsgPackPacketDecoder {
+    fn decode_text(&mut self, text: &str) -> Result<Option<SocketIoMessage>, SocketIoDecodeError> {
+        Err(SocketIoDecodeError::InvalidPacket(format!(
+            "Text frames are not supported by msgpack parser [{}]",
+            text
+        )))
+    }
+
+    fn decode_binary(
+        &mut self,
+        data: Vec<u8>,
+    ) -> Result<Option<SocketIoMessage>, SocketIoDecodeError> {
+        self.limits.check_binary_frame(&data)?;
+        let message = MsgPackParser::decode_packet_with_limits(&data, &self.limits)?;
+        self.limits.check_message(&message)?;
+        Ok(Some(message))
+    }
+}
+
+impl MsgPackParser {
+    pub fn encode_packet(message: &SocketIoMessage) -> Result<Vec<u8>, SocketIoEncodeError> {
+        encode_value_packet::<Value>(message)
+    }
+
+    /// Decodes with default [`SocketIoDecodeLimits`]
+    pub fn decode_packet(src: &[u8]) -> Result<SocketIoMessage, SocketIoDecodeError> {
+        Self::decode_packet_with_limits(src, &SocketIoDecodeLimits::default())
+    }
+
+    pub fn decode_packet_with_limits(
+        src: &[u8],
+        limits: &SocketIoDecodeLimits,
+    ) -> Result<SocketIoMessage, SocketIoDecodeError> {
+        decode_value_packet::<Value>(src, limits)
+    }
+}
+
+impl PacketValue for Value {
+    // Binary is inline, so packet type stays EVENT/ACK as it is done by the JS parser
+    const BINARY_EVENT_TYPE: u64 = PACKET_TYPE_EVENT;
+    const BINARY_ACK_TYPE: u64 = PACKET_TYPE_ACK;
+
+    fn from_u64(value: u64) -> Self {
+        Value::from(value)
+    }
+
+    fn from_i64(value: i64) -> Self {
+        Value::from(value)
+    }
+
+    fn from_bool(value: bool) -> Self {
+        Value::from(value)
+    }
+
+    fn from_text(value: &str) -> Self {
+        Value::from(value)
+    }
+
+    fn from_bytes(value: Vec<u8>) -> Self {
+        Value::Binary(value)
+    }
+
+    fn from_map(fields: Vec<(Self, Self)>) -> Self {
+        Value::Map(fields)
+    }
+
+    fn as_u64(&self) -> Option<u64> {
+        Value::as_u64(self)
+    }
+
+    fn as_i64(&self) -> Option<i64> {
+        Value::as_i64(self)
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        Value::as_bool(self)
+    }
+
+    fn as_text(&self) -> Option<&str> {
+        Value::as_str(self)
+    }
+
+    fn as_map(&self) -> Option<&Vec<(Self, Self)>> {
+        Value::as_map(self)
+    }
+
+    fn as_bytes_mut(&mut self) -> Option<&mut Vec<u8>> {
+        match self {
+            Value::Binary(data) => Some(data),
+            _ => None,
+        }
+    }
+
+    fn as_array_mut(&mut self) -> Option<&mut Vec<Self>> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    fn as_map_mut(&mut self) -> Option<&mut Vec<(Self, Self)>> {
+        match self {
+            Value::Map(fields) => Some(fields),
+            _ => None,
+        }
+    }
+
+    fn into_array(self) -> Option<Vec<Self>> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    fn into_map(self) -> Option<Vec<(Self, Self)>> {
+        match self {
+            Value::Map(fields) => Some(fields),
+            _ => None,
+        }
+    }
+
+    fn write(&self) -> Vec<u8> {
+        let mut result = Vec::new();
+        rmpv::encode::write_value(&mut result, self).unwrap();
+        result
+    }
+
+    fn read(src: &[u8]) -> Result<Self, String> {
+        rmpv::decode::read_value(&mut &src[..]).map_err(|err| format!("Invalid msgpack: {}", err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Byte vectors are produced by socket.io-msgpack-parser (notepack.io) in Node.js
+
+    #[test]
+    fn test_server_event() {
+        // { type: 2, data: ["foo", "bar"], nsp: "/" }
+        let js_bytes = [
+            0x83, 0xa4, b't', b'y', b'p', b'e', 0x02, 0xa4, b'd', b'a', b't', b'a', 0x92, 0xa3,
+            b'f', b'o', b'o', 0xa3, b'b', b'a', b'r', 0xa3, b'n', b's', b'p', 0xa1, b'/',
+        ];
+
+        let message = SocketIoMessage::Event {
+            namespace: "/".into(),
+            event_name: "foo".into(),
+            data: "\"bar\"".into(),
+            rest_args: Vec::new(),
+            ack: None,
+        };
+
+        assert_eq!(MsgPackParser::encode_packet(&message).unwrap(), js_bytes);
+
+        match MsgPackParser::decode_packet(&js_bytes).unwrap() {
+            SocketIoMessage::Event {
+                namespace,
+                event_name,
+                data,
+                ack,
+                ..
+            } => {
+                assert_eq!(namespace.as_str(), "/");
+                assert_eq!(event_name.as_str(), "foo");
+                assert_eq!(data.as_str(), "\"bar\"");
+                assert!(ack.is_none());
+            }
+            _ => panic!("Invalid message"),
+        }
+    }
+
+    #[test]
+    fn test_server_event_with_ack_to_custom_namespace() {
+        // { type: 2, data: ["foo", { a: 1 }], nsp: "/admin", id: 12 }
+        let js_bytes = [
+            0x84, 0xa4, b't', b'y', b'p', b'e', 0x02, 0xa4, b'd', b'a', b't', b'a', 0x92, 0xa3,
+            b'f', b'o', b'o', 0x81, 0xa1, b'a', 0x01, 0xa3, b'n', b's', b'p', 0xa6, b'/', b'a',
+            b'd', b'm', b'i', b'n', 0xa2, b'i', b'd', 0x0c,
+        ];
+
+        let message = SocketIoMessage::Event {
+            namespace: "/admin".into(),
+            event_name: "foo".into(),
+            data: r#"{"a":1}"#.into(),
+            rest_args: Vec::new(),
+            ack: Some(12),
+        };
+
+        assert_eq!(MsgPackParser::encode_packet(&message).unwrap(), js_bytes);
+
+        match MsgPackParser::decode_packet(&js_bytes).unwrap() {
+            SocketIoMessage::Event {
+                namespace,
+                event_name,
+                data,
+                ack,
+                ..
+            } => {
+                assert_eq!(namespace.as_str(), "/admin");
+                assert_eq!(event_name.as_str(), "foo");
+                assert_eq!(data.as_str(), r#"{"a":1}"#);
+                assert_eq!(ack, Some(12));
+            }
+            _ => panic!("Invalid message"),
+        }
+    }
+
+    #[test]
+    fn test_client_event_with_options() {
+        // { type: 2, data: ["hello", 1.5], options: { compress: true }, nsp: "/" }
+        let js_bytes = [
+            0x84, 0xa4, b't', b'y', b'p', b'e', 0x02, 0xa4, b'd', b'a', b't', b'a', 0x92, 0xa5,
+            b'h', b'e', b'l', b'l', b'o', 0xcb, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0xa7, b'o', b'p', b't', b'i', b'o', b'n', b's', 0x81, 0xa8, b'c', b'o', b'm', b'p',
+            b'r', b'e', b's', b's', 0xc3, 0xa3, b'n', b's', b'p', 0xa1, b'/',
+        ];
+
+        match MsgPackParser::decode_packet(&js_bytes).unwrap() {
+            SocketIoMessage::Event {
+                namespace,
+                event_name,
+                data,
+                ack,
+                ..
+            } => {
+                assert_eq!(namespace.as_str(), "/");
+                assert_eq!(event_name.as_str(), "hello");
+                assert_eq!(data.as_str(), "1.5");
+                assert!(ack.is_none());
+            }
+            _ => panic!("Invalid message"),
+        }
+    }
+
+    #[test]
+    fn test_event_with_inline_binary() {
+        // { type: 2, data: ["file", Buffer.from([1, 2, 3])], nsp: "/" }
+        let js_bytes = [
+            0x83, 0xa4, b't', b'y', b'p', b'e', 0x02, 0xa4, b'd', b'a', b't', b'a', 0x92, 0xa4,
+            b'f', b'i', b'l', b'e', 0xc4, 0x03, 0x01, 0x02, 0x03, 0xa3, b'n', b's', b'p', 0xa1,
+            b'/',
+        ];
+
+        let message = SocketIoMessage::BinaryEvent {
+            namespace: "/".into(),
+            event_name: "file".into(),
+            data: r#"{"_placeholder":true,"num":0}"#.into(),
+            rest_args: Vec::new(),
+            ack: None,
+            attachments: vec![vec![1, 2, 3]],
+        };
+
+        assert_eq!(MsgPackParser::encode_packet(&message).unwrap(), js_bytes);
+
+        match MsgPackParser::decode_packet(&js_bytes).unwrap() {
+            SocketIoMessage::BinaryEvent {
+                event_name,
+                data,
+                attachments,
+                ..
+            } => {
+                assert_eq!(event_name.as_str(), "file");
+                assert_eq!(data.as_str(), r#"{"_placeholder":true,"num":0}"#);
+                assert_eq!(attachments, vec![vec![1, 2, 3]]);
+            }
+            _ => panic!("Invalid message"),
+        }
+    }
+
+    #[test]
+    fn test_connect_with_sid() {
+        // { type: 0, data: { sid: "abc" }, nsp: "/" }
+        let js_bytes = [
+            0x83, 0xa4, b't', b'y', b'p', b'e', 0x00, 0xa4, b'd', b'a', b't', b'a', 0x81, 0xa3,
+            b's', b'i', b'd', 0xa3, b'a', b'b', b'c', 0xa3, b'n', b's', b'p', 0xa1, b'/',
+        ];
+
+        let message = SocketIoMessage::Connect {
+            namespace: "/".into(),
+            sid: Some("abc".into()),
+            auth: None,
+        };
+
+        assert_eq!(MsgPackParser::encode_packet(&message).unwrap(), js_bytes);
+
+        match MsgPackParser::decode_packet(&js_bytes).unwrap() {
+            SocketIoMessage::Connect { namespace, sid, .. } => {
+                assert_eq!(namespace.as_str(), "/");
+                assert_eq!(sid.unwrap().as_str(), "abc");
+            }
+            _ => panic!("Invalid message"),
+        }
+    }
+
+    #[test]
+    fn test_disconnect() {
+        // { type: 1, nsp: "/admin" }
+        let js_bytes = [
+            0x82, 0xa4, b't', b'y', b'p', b'e', 0x01, 0xa3, b'n', b's', b'p', 0xa6, b'/', b'a',
+            b'd', b'm', b'i', b'n',
+        ];
+
+        let message = SocketIoMessage::Disconnect {
+            namespace: "/admin".into(),
+        };
+
+        assert_eq!(MsgPackParser::encode_packet(&message).unwrap(), js_bytes);
+
+        match MsgPackParser::decode_packet(&js_bytes).unwrap() {
+            SocketIoMessage::Disconnect { namespace } => {
+                assert_eq!(namespace.as_str(), "/admin");
+            }
+            _ => panic!("Invalid message"),
+        }
+    }
+
+    #[test]
+    fn test_connect_error() {
+        // { type: 4, data: { message: "Not authorized" }, nsp: "/" }
+        let js_bytes = [
+            0x83, 0xa4, b't', b'y', b'p', b'e', 0x04, 0xa4, b'd', b'a', b't', b'a', 0x81, 0xa7,
+            b'm', b'e', b's', b's', b'a', b'g', b'e', 0xae, b'N', b'o', b't', b' ', b'a', b'u',
+            b't', b'h', b'o', b'r', b'i', b'z', b'e', b'd', 0xa3, b'n', b's', b'p', 0xa1, b'/',
+        ];
+
+        let message = SocketIoMessage::ConnectError {
+            namespace: "/".into(),
+            message: "Not authorized".into(),
+        };
+
+        assert_eq!(MsgPackParser::encode_packet(&message).unwrap(), js_bytes);
+
+        match MsgPackParser::decode_packet(&js_bytes).unwrap() {
+            SocketIoMessage::ConnectError { namespace, message } => {
+                assert_eq!(namespace.as_str(), "/");
+                assert_eq!(message.as_str(), "Not authorized");
+            }
+            _ => panic!("Invalid message"),
+        }
+    }
+
+    #[test]
+    fn test_invalid_packet() {
+        assert!(MsgPackParser::decode_packet(&[0x92, 0x01, 0x02]).is_err());
+        // { type: 2, data: "foo", nsp: "/" }
+        let js_bytes = [
+            0x83, 0xa4, b't', b'y', b'p', b'e', 0x02, 0xa4, b'd', b'a', b't', b'a', 0xa3, b'f',
+            b'o', b'o', 0xa3, b'n', b's', b'p', 0xa1, b'/',
+        ];
+        assert!(MsgPackParser::decode_packet(&js_bytes).is_err());
+    }
+
+    #[test]
+    fn test_decode_limits() {
+        let limits = SocketIoDecodeLimits {
+            max_json_depth: 3,
+            max_event_arguments: 2,
+            max_event_name_length: 5,
+            max_namespace_length: 6,
+            ..Default::default()
+        };
+
+        let decode = |message: &SocketIoMessage| {
+            let packet = MsgPackParser::encode_packet(message).unwrap();
+            MsgPackParser
+                .create_decoder(limits.clone())
+                .decode_binary(packet)
+        };
+
+        let event = |namespace: &'static str, event_name: &'static str, data: &'static str| {
+            SocketIoMessage::Event {
+                namespace: namespace.into(),
+                event_name: event_name.into(),
+                data: data.into(),
+                rest_args: Vec::new(),
+                ack: None,
+            }
+        };
+
+        assert!(decode(&event("/admin", "foo", r#"{"a":[1]},2"#)).is_ok());
+
+        assert_eq!(
+            decode(&event("/administrator", "foo", "")).err().unwrap(),
+            SocketIoDecodeError::NamespaceTooLong {
+                size: 14,
+                max_size: 6
+            }
+        );
+        assert_eq!(
+            decode(&event("/", "foo", r#"{"a":[[1]]}"#)).err().unwrap(),
+            SocketIoDecodeError::JsonTooDeep { max_depth: 3 }
+        );
+        assert_eq!(
+            decode(&event("/", "foo", "1,2,3")).err().unwrap(),
+            SocketIoDecodeError::TooManyArguments { max_count: 2 }
+        );
+        assert_eq!(
+            decode(&event("/", "foobar", "")).err().unwrap(),
+            SocketIoDecodeError::EventNameTooLong {
+                size: 6,
+                max_size: 5
+            }
+        );
+
+        let binary_event = SocketIoMessage::BinaryEvent {
+            namespace: "/".into(),
+            event_name: "foo".into(),
+            data: (0..11)
+                .map(|num| format!(r#"{{"_placeholder":true,"num":{}}}"#, num))
+                .collect::<Vec<_>>()
+                .join(",")
+                .into(),
+            rest_args: Vec::new(),
+            ack: None,
+            attachments: (0..11).map(|num| vec![num]).collect(),
+        };
+
+        let limits = SocketIoDecodeLimits {
+            max_event_arguments: 16,
+            ..Default::default()
+        };
+        let packet = MsgPackParser::encode_packet(&binary_event).unwrap();
+
+        assert_eq!(
+            MsgPackParser
+                .create_decoder(limits)
+                .decode_binary(packet)
+                .err()
+                .unwrap(),
+            SocketIoDecodeError::TooManyBinaryAttachments {
+                count: 11,
+                max_count: 10
+            }
+        );
+    }
+
+    #[test]
+    fn test_deep_value_tree_is_rejected_before_json() {
+        let mut nested = Value::from(1);
+
+        for _ in 0..100 {
+            nested = Value::Array(vec![nested]);
+        }
+
+        let packet = Value::Map(vec![
+            (Value::from("type"), Value::from(PACKET_TYPE_EVENT)),
+            (
+                Value::from("data"),
+                Value::Array(vec![Value::from("foo"), nested]),
+            ),
+            (Value::from("nsp"), Value::from("/")),
+        ])
+        .write();
+
+        assert_eq!(
+            MsgPackParser::decode_packet(&packet).err().unwrap(),
+            SocketIoDecodeError::JsonTooDeep { max_depth: 32 }
+        );
+
+        let limits = SocketIoDecodeLimits {
+            max_json_depth: 128,
+            ..Default::default()
+        };
+        assert!(MsgPackParser::decode_packet_with_limits(&packet, &limits).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_event_data_is_error() {
+        let message = SocketIoMessage::Event {
+            namespace: "/".into(),
+            event_name: "foo".into(),
+            data: "{not json".into(),
+            rest_args: Vec::new(),
+            ack: None,
+        };
+
+        assert!(matches!(
+            MsgPackParser::encode_packet(&message),
+            Err(SocketIoEncodeError::InvalidEventData(_))
+        ));
+        assert!(MsgPackParser.encode_to_engine_payload(&message).is_err());
+    }
+}
@@ -0,0 +1,293 @@
+use core::str;
+
+use my_json::json_reader::{AsJsonSlice, JsonArrayIterator, JsonFirstLineIterator};
+
+pub struct SocketIoPayloadData<'s> {
+    pub namespace: &'s str,
+    pub data: Option<&'s str>,
+    pub ack: Option<i64>,
+}
+
+impl<'s> SocketIoPayloadData<'s> {
+    pub fn get_field(&self, field_name: &str) -> Option<String> {
+        match self.try_get_field(field_name) {
+            Ok(result) => result,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Field value must be a string
+    pub fn try_get_field(&self, field_name: &str) -> Result<Option<String>, String> {
+        let data = match self.data {
+            Some(data) => data,
+            None => return Ok(None),
+        };
+
+        let first_line_iterator = JsonFirstLineIterator::new(data.as_bytes());
+
+        while let Some(itm) = first_line_iterator.get_next() {
+            let (name, value) = itm.map_err(|_| {
+                format!("Can not extract '{field_name}' field from data [{}]", data)
+            })?;
+
+            let name = match name.as_str() {
+                Some(name) => name,
+                None => continue,
+            };
+
+            if name.as_str() == field_name {
+                return match value.as_str() {
+                    Some(value) => Ok(Some(value.to_string())),
+                    None => Err(format!(
+                        "Field '{field_name}' must be String. Data [{}]",
+                        data
+                    )),
+                };
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Returns event name and the first argument after it as raw JSON
+    pub fn get_event_data(&self) -> Option<(String, String)> {
+        match self.try_get_event_data() {
+            Ok(result) => result,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    pub fn try_get_event_data(&self) -> Result<Option<(String, String)>, String> {
+        Ok(self.try_get_event_args()?.map(first_arg))
+    }
+
+    /// Returns event name and all the arguments after it, each one is raw JSON
+    pub fn get_event_args(&self) -> Option<(String, Vec<String>)> {
+        match self.try_get_event_args() {
+            Ok(result) => result,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    pub fn try_get_event_args(&self) -> Result<Option<(String, Vec<String>)>, String> {
+        self.read_event_data(false)
+    }
+
+    /// Ack arguments are not required to start with a string. If they do not, event name is empty and the first argument is returned as data
+    pub fn get_ack_data(&self) -> Option<(String, String)> {
+        match self.try_get_ack_data() {
+            Ok(result) => result,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    pub fn try_get_ack_data(&self) -> Result<Option<(String, String)>, String> {
+        Ok(self.try_get_ack_args()?.map(first_arg))
+    }
+
+    pub fn get_ack_args(&self) -> Option<(String, Vec<String>)> {
+        match self.try_get_ack_args() {
+            Ok(result) => result,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    pub fn try_get_ack_args(&self) -> Result<Option<(String, Vec<String>)>, String> {
+        self.read_event_data(true)
+    }
+
+    fn read_event_data(&self, is_ack: bool) -> Result<Option<(String, Vec<String>)>, String> {
+        let data = match self.data {
+            Some(data) => data,
+            None => return Ok(None),
+        };
+
+        let array_iterator = JsonArrayIterator::new(data.as_slice())
+            .map_err(|_| format!("Can not extract event data from data [{}]", data))?;
+
+        let mut args = Vec::new();
+
+        let name = match array_iterator.get_next() {
+            Some(name) => name,
+            None if is_ack => return Ok(Some((String::new(), Vec::new()))),
+            None => {
+                return Err(format!(
+                    "No name found during extracting event name from data [{}]",
+                    data
+                ))
+            }
+        };
+
+        let name = name.map_err(|err| {
+            format!(
+                "Can not extract event name from data [{}]. Error: {:?}",
+                data, err
+            )
+        })?;
+
+        let name = match name.as_str() {
+            Some(name) => name.to_string(),
+            None if is_ack => {
+                if let Some(arg) = name.as_raw_str() {
+                    args.push(arg.to_string());
+                }
+
+                String::new()
+            }
+            None => {
+                return Err(format!(
+                    "Can not extract event name from data [{}]. Event name must be String",
+                    data
+                ))
+            }
+        };
+
+        while let Some(arg) = array_iterator.get_next() {
+            let arg = arg.map_err(|err| {
+                format!(
+                    "Can not extract event payload from data [{}]. Error: {:?}",
+                    data, err
+                )
+            })?;
+
+            if let Some(arg) = arg.as_raw_str() {
+                args.push(arg.to_string());
+            }
+        }
+
+        Ok(Some((name, args)))
+    }
+}
+
+fn first_arg((name, args): (String, Vec<String>)) -> (String, String) {
+    (name, args.into_iter().next().unwrap_or_default())
+}
+
+pub fn deserialize_data(value: &str) -> SocketIoPayloadData {
+    match try_deserialize_data(value) {
+        Ok(result) => result,
+        Err(err) => panic!("{}", err),
+    }
+}
+
+pub fn try_deserialize_data(value: &str) -> Result<SocketIoPayloadData, String> {
+    let (namespace, _, data) = read_name_space_and_data_position(value)?;
+
+    Ok(SocketIoPayloadData {
+        namespace,
+        data,
+        ack: None,
+    })
+}
+
+pub fn deserialize_event_data(value: &str) -> SocketIoPayloadData {
+    match try_deserialize_event_data(value) {
+        Ok(result) => result,
+        Err(err) => panic!("{}", err),
+    }
+}
+
+pub fn try_deserialize_event_data(value: &str) -> Result<SocketIoPayloadData, String> {
+    let (namespace, ack, data) = read_name_space_and_data_position(value)?;
+
+    Ok(SocketIoPayloadData {
+        namespace,
+        data,
+        ack,
+    })
+}
+
+/// Reads `<count>-` header of BINARY_EVENT (`5`) and BINARY_ACK (`6`) socket.io packets
+pub fn read_binary_attachments_count(value: &str) -> Option<usize> {
+    match value.chars().next()? {
+        '5' | '6' => {}
+        _ => return None,
+    }
+
+    let value = &value[1..];
+    let index = find_end_of_ack_id(value);
+
+    if !value[index..].starts_with('-') {
+        return None;
+    }
+
+    value[..index].parse().ok()
+}
+
+/// Skips `<count>-` part of BINARY_EVENT and BINARY_ACK packets. Value must not contain packet type
+pub fn skip_binary_attachments_header(value: &str) -> &str {
+    let index = find_end_of_ack_id(value);
+
+    match value[index..].strip_prefix('-') {
+        Some(value) => value,
+        None => value,
+    }
+}
+
+type NamespaceAckAndData<'s> = (&'s str, Option<i64>, Option<&'s str>);
+
+fn read_name_space_and_data_position(value: &str) -> Result<NamespaceAckAndData, String> {
+    if value.is_empty() {
+        return Ok(("/", None, None));
+    }
+
+    let index = find_end_of_namespace(value);
+
+    let namespace = if index == 0 { "/" } else { &value[..index] };
+
+    if index == value.len() {
+        return Ok((namespace, None, None));
+    }
+
+    let mut data = &value[index..];
+
+    if data.starts_with(',') {
+        data = &data[1..];
+    }
+
+    if data.is_empty() {
+        return Ok((namespace, None, None));
+    }
+
+    let (ack, data) = get_ack_and_data(data)?;
+
+    Ok((namespace, ack, data))
+}
+
+fn get_ack_and_data(data: &str) -> Result<(Option<i64>, Option<&str>), String> {
+    let index = find_end_of_ack_id(data);
+
+    if index == 0 {
+        return Ok((None, Some(data)));
+    }
+
+    let ack = data[..index]
+        .parse()
+        .map_err(|_| format!("Invalid ack id [{}]", &data[..index]))?;
+
+    Ok((Some(ack), Some(&data[index..])))
+}
+
+fn find_end_of_ack_id(value: &str) -> usize {
+    let mut index = 0;
+
+    for c in value.chars() {
+        if !c.is_digit(10) {
+            return index;
+        }
+        index += 1;
+    }
+
+    index
+}
+
+/// Returns byte index
+fn find_end_of_namespace(value: &str) -> usize {
+    for (index, c) in value.char_indices() {
+        if c == ',' || c == '{' || c == '[' || c.is_ascii_digit() {
+            return index;
+        }
+    }
+
+    value.len()
+}
@@ -0,0 +1,120 @@
+use crate::{EngineIoFrame, SocketIoEventParameter};
+
+#[derive(Debug, Default)]
+pub struct SocketIoPayload {
+    pub text_frame: String,
+    pub binary_frames: Vec<Vec<u8>>,
+}
+
+impl SocketIoPayload {
+    pub fn new() -> Self {
+        SocketIoPayload {
+            text_frame: String::new(),
+            binary_frames: Vec::new(),
+        }
+    }
+
+    /// Text frame goes first followed by binary attachments
+    pub fn to_engine_frames(&self) -> Vec<EngineIoFrame> {
+        let mut result = Vec::with_capacity(self.binary_frames.len() + 1);
+
+        if !self.text_frame.is_empty() {
+            result.push(EngineIoFrame::Text(self.text_frame.clone()));
+        }
+
+        for binary_frame in &self.binary_frames {
+            result.push(EngineIoFrame::Binary(binary_frame.clone()));
+        }
+
+        result
+    }
+}
+
+pub fn serialize_data(out: &mut SocketIoPayload, namespace: &str, data: Option<(&str, &str)>) {
+    if namespace != "/" {
+        out.text_frame.push_str(namespace);
+
+        out.text_frame.push(',');
+    }
+
+    if let Some(data) = data {
+        out.text_frame.push_str("{\"");
+        out.text_frame.push_str(data.0);
+        out.text_frame.push_str("\":\"");
+        out.text_frame.push_str(data.1);
+        out.text_frame.push_str("\"}");
+    }
+}
+
+pub fn serialize_binary_attachments(out: &mut SocketIoPayload, attachments: &[Vec<u8>]) {
+    out.text_frame.push_str(&attachments.len().to_string());
+    out.text_frame.push('-');
+    out.binary_frames.extend(attachments.iter().cloned());
+}
+
+pub fn serialize_event_data(
+    out: &mut SocketIoPayload,
+    namespace: &str,
+    event_name: &str,
+    data: &str,
+    ack: Option<i64>,
+) {
+    if namespace != "/" {
+        out.text_frame.push_str(namespace);
+        out.text_frame.push(',');
+    }
+    if let Some(ack) = ack {
+        out.text_frame.push_str(&ack.to_string());
+    }
+
+    // Acks may have no leading string argument
+    if event_name.is_empty() {
+        out.text_frame.push('[');
+        out.text_frame.push_str(data);
+        out.text_frame.push(']');
+        return;
+    }
+
+    out.text_frame.push('[');
+    out.text_frame
+        .push_str(&serde_json::to_string(event_name).unwrap());
+
+    if data.len() > 0 {
+        out.text_frame.push_str(",");
+        out.text_frame.push_str(data);
+    }
+    out.text_frame.push_str("]");
+}
+
+/// Renders params as comma separated JSON. Binary params are replaced with placeholders and returned as attachments
+pub fn serialize_event_params(params: Vec<SocketIoEventParameter>) -> (String, Vec<Vec<u8>>) {
+    let mut data = String::new();
+    let mut attachments = Vec::new();
+
+    for param in params {
+        if !data.is_empty() {
+            data.push(',');
+        }
+
+        match param {
+            SocketIoEventParameter::String(value) => {
+                data.push_str(&serde_json::to_string(value.as_str()).unwrap());
+            }
+            SocketIoEventParameter::Binary(value) => {
+                data.push_str(r#"{"_placeholder":true,"num":"#);
+                data.push_str(&attachments.len().to_string());
+                data.push('}');
+                attachments.push(value);
+            }
+            SocketIoEventParameter::Number(value)
+            | SocketIoEventParameter::Object(value)
+            | SocketIoEventParameter::Array(value) => data.push_str(&value),
+            SocketIoEventParameter::Bool(value) => {
+                data.push_str(if value { "true" } else { "false" })
+            }
+            SocketIoEventParameter::Null => data.push_str("null"),
+        }
+    }
+
+    (data, attachments)
+}
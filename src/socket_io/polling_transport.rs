@@ -0,0 +1,679 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use axum::{
+    extract::{DefaultBodyLimit, RawQuery, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use tokio::sync::{mpsc, Notify};
+
+use crate::{
+    decode_polling_payload, encode_polling_frame, DefaultParser, EngineIoError, EngineIoTransport,
+    HandshakeRequest, InMemorySessionStore, OutgoingPayload, RandomSidGenerator, SessionStore,
+    SessionSweeper, SidGenerator, SocketIoContract, SocketIoContractDecoder, SocketIoDecodeLimits,
+    SocketIoEncodeError, SocketIoMessage, SocketIoParser, SocketIoPayload, SocketIoSettings,
+};
+
+const RECORD_SEPARATOR: char = '\x1e';
+
+struct PollingSession<D: crate::SocketIoPacketDecoder> {
+    outgoing: Mutex<VecDeque<String>>,
+    notify: Notify,
+    drained: Notify,
+    incoming: mpsc::UnboundedSender<SocketIoContract>,
+    decoder: Mutex<SocketIoContractDecoder<D>>,
+    polling: AtomicBool,
+    closed: AtomicBool,
+}
+
+impl<D: crate::SocketIoPacketDecoder> PollingSession<D> {
+    fn enqueue(&self, packet: String) {
+        self.outgoing.lock().unwrap().push_back(packet);
+        self.notify.notify_one();
+    }
+
+    fn drain(&self) -> Option<String> {
+        let mut outgoing = self.outgoing.lock().unwrap();
+
+        if outgoing.is_empty() {
+            return None;
+        }
+
+        let packets: Vec<String> = outgoing.drain(..).collect();
+        self.drained.notify_waiters();
+        Some(packets.join(&RECORD_SEPARATOR.to_string()))
+    }
+
+    fn decode_packets(&self, body: &str) -> Result<Vec<SocketIoContract>, EngineIoError> {
+        let packets = decode_polling_payload(body).map_err(|_| EngineIoError::BadRequest)?;
+
+        let mut decoder = self.decoder.lock().unwrap();
+        let mut result = Vec::new();
+
+        for packet in packets {
+            let contract = decoder
+                .decode_packet(packet)
+                .map_err(|_| EngineIoError::BadRequest)?;
+
+            if let Some(contract) = contract {
+                result.push(contract);
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+pub struct PollingConnection<P: SocketIoParser = DefaultParser> {
+    pub sid: String,
+    /// Query of the handshake request
+    pub query: HashMap<String, String>,
+    /// Headers of the handshake request. Names are lowercase, values which are not valid strings are skipped
+    pub headers: HashMap<String, String>,
+    pub incoming: mpsc::UnboundedReceiver<SocketIoContract>,
+    session: Arc<PollingSession<P::Decoder>>,
+    parser: Arc<P>,
+}
+
+impl<P: SocketIoParser> PollingConnection<P> {
+    pub fn send(&self, payload: &SocketIoPayload) {
+        for frame in payload.to_engine_frames() {
+            self.session.enqueue(encode_polling_frame(&frame));
+        }
+    }
+
+    /// Compression of polling responses is up to HTTP layer, `compress` flag is not used
+    pub fn send_outgoing(&self, outgoing: &OutgoingPayload) {
+        self.send(&outgoing.payload);
+    }
+
+    pub fn send_contract(&self, contract: &SocketIoContract) {
+        self.send(&contract.serialize());
+    }
+
+    pub fn send_message(&self, message: &SocketIoMessage) -> Result<(), SocketIoEncodeError> {
+        self.send(&self.parser.encode_to_engine_payload(message)?);
+        Ok(())
+    }
+
+    pub fn close(&self) {
+        self.session.closed.store(true, Ordering::SeqCst);
+        self.session.enqueue("1".to_string());
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.session.closed.load(Ordering::SeqCst)
+    }
+
+    /// Packets waiting for the next GET request
+    pub fn pending_len(&self) -> usize {
+        self.session.outgoing.lock().unwrap().len()
+    }
+
+    /// Waits until the client takes all the pending packets
+    pub async fn drained(&self) {
+        loop {
+            let notified = self.session.drained.notified();
+
+            if self.pending_len() == 0 || self.is_closed() {
+                return;
+            }
+
+            notified.await;
+        }
+    }
+}
+
+pub struct PollingTransport<P: SocketIoParser = DefaultParser> {
+    settings: SocketIoSettings,
+    limits: SocketIoDecodeLimits,
+    parser: Arc<P>,
+    sessions: InMemorySessionStore<Arc<PollingSession<P::Decoder>>>,
+    sid_generator: Box<dyn SidGenerator + Send + Sync>,
+    new_connections: mpsc::UnboundedSender<PollingConnection<P>>,
+}
+
+impl PollingTransport<DefaultParser> {
+    pub fn new(
+        settings: SocketIoSettings,
+    ) -> (Arc<Self>, mpsc::UnboundedReceiver<PollingConnection>) {
+        Self::new_with_sid_generator(settings, RandomSidGenerator)
+    }
+
+    pub fn new_with_sid_generator(
+        settings: SocketIoSettings,
+        sid_generator: impl SidGenerator + Send + Sync + 'static,
+    ) -> (Arc<Self>, mpsc::UnboundedReceiver<PollingConnection>) {
+        Self::new_with_parser(settings, DefaultParser, sid_generator)
+    }
+}
+
+impl<P> PollingTransport<P>
+where
+    P: SocketIoParser + Send + Sync + 'static,
+    P::Decoder: Send + 'static,
+{
+    pub fn new_with_parser(
+        settings: SocketIoSettings,
+        parser: P,
+        sid_generator: impl SidGenerator + Send + Sync + 'static,
+    ) -> (Arc<Self>, mpsc::UnboundedReceiver<PollingConnection<P>>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        let idle_timeout = settings.ping_interval + settings.ping_timeout;
+
+        let result = Self {
+            limits: SocketIoDecodeLimits::from_settings(&settings),
+            settings,
+            parser: Arc::new(parser),
+            sessions: InMemorySessionStore::new(idle_timeout),
+            sid_generator: Box::new(sid_generator),
+            new_connections: sender,
+        };
+
+        (Arc::new(result), receiver)
+    }
+
+    /// Router with `/socket.io/` long-polling endpoints. Can be merged or nested into application router.
+    pub fn router(self: &Arc<Self>) -> Router {
+        Router::new()
+            .route("/socket.io", get(handle_get::<P>).post(handle_post::<P>))
+            .route("/socket.io/", get(handle_get::<P>).post(handle_post::<P>))
+            .layer(DefaultBodyLimit::max(self.limits.max_payload_size))
+            .with_state(self.clone())
+    }
+
+    pub fn sessions_count(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// Closes sessions which had no requests during `ping_interval + ping_timeout`. Returns their sids.
+    pub fn remove_idle_sessions(&self) -> Vec<String> {
+        let mut result = Vec::new();
+
+        for (sid, session) in self.sessions.remove_expired() {
+            session.closed.store(true, Ordering::SeqCst);
+            let _ = session.incoming.send(SocketIoContract::Close);
+            result.push(sid);
+        }
+
+        result
+    }
+
+    /// Calls [`Self::remove_idle_sessions`] every `ping_interval` until the sweeper is stopped or dropped
+    pub fn spawn_session_sweeper(self: &Arc<Self>) -> SessionSweeper {
+        let transport = Arc::downgrade(self);
+
+        SessionSweeper::spawn(self.settings.ping_interval, move || {
+            if let Some(transport) = transport.upgrade() {
+                transport.remove_idle_sessions();
+            }
+        })
+    }
+
+    fn get_session(&self, sid: &str) -> Option<Arc<PollingSession<P::Decoder>>> {
+        self.sessions.get(sid)
+    }
+
+    fn remove_session(&self, sid: &str) {
+        self.sessions.remove(sid);
+    }
+
+    fn handshake(&self, request: &HandshakeRequest, headers: &HeaderMap) -> Response {
+        let sid = self.sid_generator.generate_sid();
+
+        let (incoming_sender, incoming_receiver) = mpsc::unbounded_channel();
+
+        let session = Arc::new(PollingSession {
+            outgoing: Mutex::new(VecDeque::new()),
+            notify: Notify::new(),
+            drained: Notify::new(),
+            incoming: incoming_sender,
+            decoder: Mutex::new(SocketIoContractDecoder::new(
+                self.parser.as_ref(),
+                self.limits.clone(),
+            )),
+            polling: AtomicBool::new(false),
+            closed: AtomicBool::new(false),
+        });
+
+        let connection = PollingConnection {
+            sid: sid.clone(),
+            query: request.query.clone(),
+            headers: headers
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.as_str().to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect(),
+            incoming: incoming_receiver,
+            session: session.clone(),
+            parser: self.parser.clone(),
+        };
+
+        if self.new_connections.send(connection).is_err() {
+            return engine_io_error(EngineIoError::BadRequest);
+        }
+
+        self.sessions.insert(sid.clone(), session);
+
+        let open_model = request.create_open_model(sid, &self.settings);
+        text_response(
+            StatusCode::OK,
+            SocketIoContract::Open(open_model).serialize().text_frame,
+        )
+    }
+}
+
+/// Marks the session as being polled. Reset on drop, so an aborted request does not block the next ones
+struct PollingGuard<'s> {
+    polling: &'s AtomicBool,
+}
+
+impl<'s> PollingGuard<'s> {
+    fn acquire(polling: &'s AtomicBool) -> Option<Self> {
+        if polling.swap(true, Ordering::SeqCst) {
+            return None;
+        }
+
+        Some(Self { polling })
+    }
+}
+
+impl Drop for PollingGuard<'_> {
+    fn drop(&mut self) {
+        self.polling.store(false, Ordering::SeqCst);
+    }
+}
+
+async fn handle_get<P>(
+    State(transport): State<Arc<PollingTransport<P>>>,
+    RawQuery(query): RawQuery,
+    headers: HeaderMap,
+) -> Response
+where
+    P: SocketIoParser + Send + Sync + 'static,
+    P::Decoder: Send + 'static,
+{
+    let request = match parse_request(query, "GET") {
+        Ok(request) => request,
+        Err(err) => return engine_io_error(err),
+    };
+
+    let sid = match &request.sid {
+        Some(sid) => sid,
+        None => return transport.handshake(&request, &headers),
+    };
+
+    let session = match transport.get_session(sid) {
+        Some(session) => session,
+        None => return engine_io_error(EngineIoError::UnknownSid),
+    };
+
+    let _polling = match PollingGuard::acquire(&session.polling) {
+        Some(guard) => guard,
+        None => return engine_io_error(EngineIoError::BadRequest),
+    };
+
+    let payload = loop {
+        if let Some(payload) = session.drain() {
+            break payload;
+        }
+
+        let wait =
+            tokio::time::timeout(transport.settings.ping_interval, session.notify.notified());
+
+        if wait.await.is_err() {
+            break SocketIoContract::Noop.serialize().text_frame;
+        }
+    };
+
+    if session.closed.load(Ordering::SeqCst) {
+        transport.remove_session(sid);
+    }
+
+    text_response(StatusCode::OK, payload)
+}
+
+async fn handle_post<P>(
+    State(transport): State<Arc<PollingTransport<P>>>,
+    RawQuery(query): RawQuery,
+    body: String,
+) -> Response
+where
+    P: SocketIoParser + Send + Sync + 'static,
+    P::Decoder: Send + 'static,
+{
+    let request = match parse_request(query, "POST") {
+        Ok(request) => request,
+        Err(err) => return engine_io_error(err),
+    };
+
+    let sid = request.sid.as_deref().unwrap_or_default();
+
+    let session = match transport.get_session(sid) {
+        Some(session) => session,
+        None => return engine_io_error(EngineIoError::UnknownSid),
+    };
+
+    if transport
+        .limits
+        .check_polling_body(body.as_bytes())
+        .is_err()
+    {
+        return text_response(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "Payload Too Large".to_string(),
+        );
+    }
+
+    let contracts = match session.decode_packets(&body) {
+        Ok(contracts) => contracts,
+        Err(err) => return engine_io_error(err),
+    };
+
+    for contract in contracts {
+        let is_close = matches!(contract, SocketIoContract::Close);
+
+        let _ = session.incoming.send(contract);
+
+        if is_close {
+            session.closed.store(true, Ordering::SeqCst);
+            session.notify.notify_one();
+            transport.remove_session(sid);
+            break;
+        }
+    }
+
+    text_response(StatusCode::OK, "ok".to_string())
+}
+
+fn parse_request(query: Option<String>, method: &str) -> Result<HandshakeRequest, EngineIoError> {
+    let request = HandshakeRequest::from_query_string(query.as_deref().unwrap_or_default())?;
+
+    if request.transport != EngineIoTransport::Polling {
+        return Err(EngineIoError::TransportUnknown);
+    }
+
+    request.validate_method(method)?;
+
+    Ok(request)
+}
+
+fn engine_io_error(err: EngineIoError) -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        [(header::CONTENT_TYPE, "application/json")],
+        err.to_json(),
+    )
+        .into_response()
+}
+
+fn text_response(status_code: StatusCode, body: String) -> Response {
+    (
+        status_code,
+        [(header::CONTENT_TYPE, "text/plain; charset=UTF-8")],
+        body,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::SocketIoMessage;
+
+    fn create_transport() -> (
+        Arc<PollingTransport>,
+        mpsc::UnboundedReceiver<PollingConnection>,
+    ) {
+        PollingTransport::new(SocketIoSettings {
+            ping_interval: Duration::from_millis(500),
+            ping_timeout: Duration::from_millis(500),
+            max_payload_size: 1000000,
+            ..Default::default()
+        })
+    }
+
+    async fn request(router: &Router, method: &str, uri: &str, body: &str) -> (StatusCode, String) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::from(body.to_string()))
+            .unwrap();
+
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    async fn handshake(router: &Router) -> String {
+        let (status, body) =
+            request(router, "GET", "/socket.io/?EIO=4&transport=polling", "").await;
+        assert_eq!(status, StatusCode::OK);
+
+        match SocketIoContract::deserialize(&body).unwrap() {
+            SocketIoContract::Open(model) => model.sid,
+            _ => panic!("Open packet is expected"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handshake_and_exchange() {
+        let (transport, mut connections) = create_transport();
+        let router = transport.router();
+
+        let sid = handshake(&router).await;
+
+        let mut connection = connections.recv().await.unwrap();
+        assert_eq!(connection.sid, sid);
+
+        let poll_uri = format!("/socket.io/?EIO=4&transport=polling&sid={}", sid);
+
+        let poll = {
+            let router = router.clone();
+            let poll_uri = poll_uri.clone();
+            tokio::spawn(async move { request(&router, "GET", &poll_uri, "").await })
+        };
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        connection.send_contract(&SocketIoContract::Ping { payload: None });
+        connection
+            .send_message(&SocketIoMessage::BinaryEvent {
+                namespace: "/".into(),
+                event_name: "foo".into(),
+                data: r#"{"_placeholder":true,"num":0}"#.into(),
+                rest_args: Vec::new(),
+                ack: None,
+                attachments: vec![vec![1, 2, 3]],
+            })
+            .unwrap();
+
+        let (status, body) = poll.await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body,
+            "2\x1e451-[\"foo\",{\"_placeholder\":true,\"num\":0}]\x1ebAQID"
+        );
+
+        let (status, body) = request(
+            &router,
+            "POST",
+            &poll_uri,
+            "3\x1e42[\"bar\"]\x1e451-[\"bar\",{\"_placeholder\":true,\"num\":0}]\x1ebBAU=",
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "ok");
+
+        match connection.incoming.recv().await.unwrap() {
+            SocketIoContract::Pong { payload } => assert!(payload.is_none()),
+            _ => panic!("Pong is expected"),
+        }
+
+        match connection.incoming.recv().await.unwrap() {
+            SocketIoContract::Message(SocketIoMessage::Event { event_name, .. }) => {
+                assert_eq!(event_name.as_str(), "bar")
+            }
+            _ => panic!("Event is expected"),
+        }
+
+        match connection.incoming.recv().await.unwrap() {
+            SocketIoContract::Message(SocketIoMessage::BinaryEvent { attachments, .. }) => {
+                assert_eq!(attachments, vec![vec![4, 5]])
+            }
+            _ => panic!("BinaryEvent is expected"),
+        }
+
+        let (status, body) = request(&router, "POST", &poll_uri, "1").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "ok");
+        assert_eq!(transport.sessions_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_poll_without_data_returns_noop() {
+        let (transport, mut connections) = create_transport();
+        let router = transport.router();
+
+        let sid = handshake(&router).await;
+        let _connection = connections.recv().await.unwrap();
+
+        let poll_uri = format!("/socket.io/?EIO=4&transport=polling&sid={}", sid);
+        let (status, body) = request(&router, "GET", &poll_uri, "").await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "6");
+    }
+
+    #[tokio::test]
+    async fn test_aborted_poll_releases_session() {
+        let (transport, mut connections) = create_transport();
+        let router = transport.router();
+
+        let sid = handshake(&router).await;
+        let connection = connections.recv().await.unwrap();
+
+        let poll_uri = format!("/socket.io/?EIO=4&transport=polling&sid={}", sid);
+
+        let poll = {
+            let router = router.clone();
+            let poll_uri = poll_uri.clone();
+            tokio::spawn(async move { request(&router, "GET", &poll_uri, "").await })
+        };
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let (status, _) = request(&router, "GET", &poll_uri, "").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // Client gone before the response
+        poll.abort();
+        let _ = poll.await;
+
+        connection.send_contract(&SocketIoContract::Noop);
+
+        let (status, body) = request(&router, "GET", &poll_uri, "").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "6");
+    }
+
+    #[tokio::test]
+    async fn test_session_sweeper_closes_idle_sessions() {
+        let (transport, mut connections) = create_transport();
+        let router = transport.router();
+        let sweeper = transport.spawn_session_sweeper();
+
+        handshake(&router).await;
+        let mut connection = connections.recv().await.unwrap();
+
+        // Idle timeout is ping_interval + ping_timeout = 1s
+        match connection.incoming.recv().await.unwrap() {
+            SocketIoContract::Close => {}
+            _ => panic!("Close is expected"),
+        }
+
+        assert_eq!(transport.sessions_count(), 0);
+        sweeper.stop();
+    }
+
+    #[tokio::test]
+    async fn test_unknown_sid() {
+        let (transport, _connections) = create_transport();
+        let router = transport.router();
+
+        let (status, body) = request(
+            &router,
+            "GET",
+            "/socket.io/?EIO=4&transport=polling&sid=unknown",
+            "",
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body, r#"{"code":1,"message":"Session ID unknown"}"#);
+
+        let (status, body) = request(
+            &router,
+            "POST",
+            "/socket.io/?EIO=4&transport=polling&sid=unknown",
+            "42[\"foo\"]",
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body, r#"{"code":1,"message":"Session ID unknown"}"#);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_body() {
+        let (transport, mut connections) = PollingTransport::new(SocketIoSettings {
+            ping_interval: Duration::from_millis(500),
+            ping_timeout: Duration::from_millis(500),
+            max_payload_size: 16,
+            ..Default::default()
+        });
+        let router = transport.router();
+
+        let sid = handshake(&router).await;
+        let mut connection = connections.recv().await.unwrap();
+
+        let poll_uri = format!("/socket.io/?EIO=4&transport=polling&sid={}", sid);
+        let (status, _) = request(&router, "POST", &poll_uri, "42[\"foo\",\"long payload\"]").await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+        let (status, _) = request(&router, "POST", &poll_uri, "4512-[\"foo\"]").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        assert!(connection.incoming.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_transport() {
+        let (transport, _connections) = create_transport();
+        let router = transport.router();
+
+        let (status, body) = request(&router, "GET", "/socket.io/?EIO=4&transport=flash", "").await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body, r#"{"code":0,"message":"Transport unknown"}"#);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::*;
+
+pub enum SocketIoContract {
+    Open(SocketIoHandshakeOpenModel),
+    Close,
+    Ping {
+        payload: Option<String>,
+    },
+    Pong {
+        payload: Option<String>,
+    },
+    Message(SocketIoMessage),
+    /// Produced instead of [`SocketIoContract::Message`] by decoders in raw Engine.IO mode
+    EngineMessage(EngineMessage),
+    Upgrade,
+    Noop,
+}
+
+/// Payload of Ping/Pong packets used by the transport upgrade
+pub const PROBE_PAYLOAD: &str = "probe";
+
+impl SocketIoContract {
+    pub fn ping_probe() -> Self {
+        Self::Ping {
+            payload: Some(PROBE_PAYLOAD.to_string()),
+        }
+    }
+
+    pub fn pong_probe() -> Self {
+        Self::Pong {
+            payload: Some(PROBE_PAYLOAD.to_string()),
+        }
+    }
+
+    /// Parses with default [`SocketIoDecodeLimits`]
+    pub fn deserialize(src: &str) -> Result<Self, SocketIoDecodeError> {
+        Self::deserialize_with_limits(src, &SocketIoDecodeLimits::default())
+    }
+
+    /// Engine.IO only mode: `4` packets are not parsed as Socket.IO messages
+    pub fn deserialize_raw(src: &str) -> Result<Self, SocketIoDecodeError> {
+        match src.strip_prefix('4') {
+            Some(data) => Ok(Self::EngineMessage(EngineMessage::Text(data.to_string()))),
+            None => Self::deserialize(src),
+        }
+    }
+
+    /// Checks frame size and binary attachments header against limits before parsing the frame
+    pub fn deserialize_with_limits(
+        src: &str,
+        limits: &SocketIoDecodeLimits,
+    ) -> Result<Self, SocketIoDecodeError> {
+        limits.check_text_frame(src)?;
+
+        if let Some(message) = src.strip_prefix('4') {
+            if let Some(count) = read_binary_attachments_count(message) {
+                limits.check_binary_attachments_count(count)?;
+            }
+
+            let message = SocketIoMessage::deserialize_with_limits(message, limits)?;
+            return Ok(Self::Message(message));
+        }
+
+        Ok(Self::from_engine_packet(EngineIoPacket::deserialize(src)?))
+    }
+
+    /// Engine.IO messages are not parsed as Socket.IO messages
+    pub fn from_engine_packet(packet: EngineIoPacket) -> Self {
+        match packet {
+            EngineIoPacket::Open(model) => Self::Open(model),
+            EngineIoPacket::Close => Self::Close,
+            EngineIoPacket::Ping { payload } => Self::Ping { payload },
+            EngineIoPacket::Pong { payload } => Self::Pong { payload },
+            EngineIoPacket::Message(message) => Self::EngineMessage(message),
+            EngineIoPacket::Upgrade => Self::Upgrade,
+            EngineIoPacket::Noop => Self::Noop,
+        }
+    }
+
+    pub fn serialize(&self) -> SocketIoPayload {
+        let mut result = SocketIoPayload::new();
+        match self {
+            Self::Open(model) => {
+                result.text_frame.push('0');
+
+                let json_model = serde_json::to_string(model).unwrap();
+                result.text_frame.push_str(&json_model);
+            }
+            Self::Close => {
+                result.text_frame.push('1');
+            }
+            Self::Ping { payload } => {
+                result.text_frame.push('2');
+
+                if let Some(payload) = payload {
+                    result.text_frame.push_str(payload);
+                }
+            }
+            Self::Pong { payload } => {
+                result.text_frame.push('3');
+
+                if let Some(payload) = payload {
+                    result.text_frame.push_str(payload);
+                }
+            }
+            Self::Message(msg) => {
+                result.text_frame.push('4');
+                msg.serialize(&mut result);
+            }
+            Self::EngineMessage(EngineMessage::Text(data)) => {
+                result.text_frame.push('4');
+                result.text_frame.push_str(data);
+            }
+            Self::EngineMessage(EngineMessage::Binary(data)) => {
+                result.binary_frames.push(data.clone());
+            }
+            Self::Upgrade => {
+                result.text_frame.push('5');
+            }
+            Self::Noop => {
+                result.text_frame.push('6');
+            }
+        }
+
+        result
+    }
+}
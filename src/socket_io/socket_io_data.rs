@@ -0,0 +1,224 @@
+use rust_extensions::StrOrString;
+use serde::de::IgnoredAny;
+
+pub enum SocketIoEventParameter {
+    String(StrOrString<'static>),
+    Binary(Vec<u8>),
+    /// Raw JSON number as it came in the packet to keep precision
+    Number(String),
+    Bool(bool),
+    Null,
+    /// Raw JSON object
+    Object(String),
+    /// Raw JSON array
+    Array(String),
+}
+
+impl SocketIoEventParameter {
+    pub fn unwrap_as_str(&self) -> &str {
+        match self {
+            Self::String(value) => value.as_str(),
+            _ => panic!("Expected string, found {}", self.get_type_name()),
+        }
+    }
+
+    pub fn get_type_name(&self) -> &'static str {
+        match self {
+            Self::String(_) => "string",
+            Self::Binary(_) => "binary",
+            Self::Number(_) => "number",
+            Self::Bool(_) => "bool",
+            Self::Null => "null",
+            Self::Object(_) => "object",
+            Self::Array(_) => "array",
+        }
+    }
+}
+
+impl SocketIoEventParameter {
+    /// Parses JSON array of event arguments `["foo", 1, {"a": true}]`.
+    /// Any other JSON value gives one parameter, invalid JSON gives none. Use [`Self::try_parse`] to get the error
+    pub fn parse(value: &str) -> Vec<Self> {
+        match Self::try_parse(value) {
+            Ok(result) => result,
+            Err(_) => match serde_json::from_str::<IgnoredAny>(value) {
+                Ok(_) => parse_item(value.trim()).into_iter().collect(),
+                Err(_) => Vec::new(),
+            },
+        }
+    }
+
+    /// Parses arguments the way they are stored in `SocketIoMessage::Event` data: comma separated JSON values
+    pub fn parse_event_data(data: &str) -> Vec<Self> {
+        if data.trim().is_empty() {
+            return Vec::new();
+        }
+
+        Self::parse(&format!("[{}]", data))
+    }
+
+    pub fn try_parse(value: &str) -> Result<Vec<Self>, String> {
+        if let Err(err) = serde_json::from_str::<IgnoredAny>(value) {
+            return Err(format!("Invalid JSON [{}]. Error: {}", value, err));
+        }
+
+        let value = value.trim();
+
+        let items = match value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            Some(items) => items,
+            None => return Err(format!("Arguments must be a JSON array [{}]", value)),
+        };
+
+        let mut result = Vec::new();
+
+        for item in split_array_items(items) {
+            result.push(parse_item(item)?);
+        }
+
+        Ok(result)
+    }
+}
+
+/// Input is already validated JSON, so only strings and nesting have to be tracked
+fn split_array_items(items: &str) -> Vec<&str> {
+    let mut result = Vec::new();
+
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escape = false;
+    let mut start = 0;
+
+    for (index, b) in items.bytes().enumerate() {
+        if in_string {
+            if escape {
+                escape = false;
+            } else if b == b'\\' {
+                escape = true;
+            } else if b == b'"' {
+                in_string = false;
+            }
+
+            continue;
+        }
+
+        match b {
+            b'"' => in_string = true,
+            b'[' | b'{' => depth += 1,
+            b']' | b'}' => depth -= 1,
+            b',' if depth == 0 => {
+                result.push(items[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+
+    let last = items[start..].trim();
+
+    if !last.is_empty() {
+        result.push(last);
+    }
+
+    result
+}
+
+fn parse_item(item: &str) -> Result<SocketIoEventParameter, String> {
+    let result = match item.as_bytes().first() {
+        Some(b'"') => {
+            let value: String = serde_json::from_str(item)
+                .map_err(|err| format!("Invalid string [{}]. Error: {}", item, err))?;
+            SocketIoEventParameter::String(value.into())
+        }
+        Some(b'{') => SocketIoEventParameter::Object(item.to_string()),
+        Some(b'[') => SocketIoEventParameter::Array(item.to_string()),
+        _ => match item {
+            "true" => SocketIoEventParameter::Bool(true),
+            "false" => SocketIoEventParameter::Bool(false),
+            "null" => SocketIoEventParameter::Null,
+            _ => SocketIoEventParameter::Number(item.to_string()),
+        },
+    };
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SocketIoEventParameter;
+
+    #[test]
+    fn test_parse_all_types() {
+        let result = SocketIoEventParameter::parse(
+            r#"["foo", -1.5e3, true, null, {"a":[1,"]}"]}, [1, {"b":2}], false]"#,
+        );
+
+        assert_eq!(result.len(), 7);
+        assert_eq!(result[0].unwrap_as_str(), "foo");
+
+        match &result[1] {
+            SocketIoEventParameter::Number(value) => assert_eq!(value, "-1.5e3"),
+            _ => panic!("Number is expected"),
+        }
+
+        assert!(matches!(result[2], SocketIoEventParameter::Bool(true)));
+        assert!(matches!(result[3], SocketIoEventParameter::Null));
+
+        match &result[4] {
+            SocketIoEventParameter::Object(value) => assert_eq!(value, r#"{"a":[1,"]}"]}"#),
+            _ => panic!("Object is expected"),
+        }
+
+        match &result[5] {
+            SocketIoEventParameter::Array(value) => assert_eq!(value, r#"[1, {"b":2}]"#),
+            _ => panic!("Array is expected"),
+        }
+
+        assert!(matches!(result[6], SocketIoEventParameter::Bool(false)));
+    }
+
+    #[test]
+    fn test_parse_strings_with_escapes_and_unicode() {
+        let result = SocketIoEventParameter::parse(
+            r#"["a\"b,c", "line\nbreak", "\u00e9\ud83d\ude00", "ü"]"#,
+        );
+
+        assert_eq!(result.len(), 4);
+        assert_eq!(result[0].unwrap_as_str(), "a\"b,c");
+        assert_eq!(result[1].unwrap_as_str(), "line\nbreak");
+        assert_eq!(result[2].unwrap_as_str(), "é😀");
+        assert_eq!(result[3].unwrap_as_str(), "ü");
+    }
+
+    #[test]
+    fn test_parse_event_data() {
+        let result = SocketIoEventParameter::parse_event_data(r#""bar",{"a":1}"#);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].unwrap_as_str(), "bar");
+
+        assert!(SocketIoEventParameter::parse_event_data("").is_empty());
+        assert!(SocketIoEventParameter::try_parse(r#"["foo""#).is_err());
+        assert!(SocketIoEventParameter::try_parse(r#"{"a":1}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_non_array_input() {
+        let result = SocketIoEventParameter::parse(r#""bar""#);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].unwrap_as_str(), "bar");
+
+        let result = SocketIoEventParameter::parse(r#"{"a":"b"}"#);
+        assert_eq!(result.len(), 1);
+
+        match &result[0] {
+            SocketIoEventParameter::Object(value) => assert_eq!(value, r#"{"a":"b"}"#),
+            _ => panic!("Object is expected"),
+        }
+
+        let result = SocketIoEventParameter::parse(" 42 ");
+        assert!(matches!(&result[..], [SocketIoEventParameter::Number(value)] if value == "42"));
+
+        assert!(SocketIoEventParameter::parse(r#"["foo""#).is_empty());
+        assert!(SocketIoEventParameter::parse(r#"{"a":"b""#).is_empty());
+        assert!(SocketIoEventParameter::parse("").is_empty());
+    }
+}
@@ -0,0 +1,298 @@
+use crate::{
+    read_binary_attachments_count, EngineIoPacket, EngineMessage, SocketIoContract,
+    SocketIoDecodeError, SocketIoDecodeLimits, SocketIoMessage, SocketIoPayload,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SocketIoEncodeError {
+    /// Event data is not a valid comma separated JSON arguments list
+    InvalidEventData(String),
+}
+
+/// Encoding of Socket.IO packets inside Engine.IO message packets
+pub trait SocketIoParser {
+    type Decoder: SocketIoPacketDecoder;
+
+    /// Text frame of the result has no Engine.IO message prefix `4`
+    fn encode(&self, message: &SocketIoMessage) -> Result<SocketIoPayload, SocketIoEncodeError>;
+
+    fn create_decoder(&self, limits: SocketIoDecodeLimits) -> Self::Decoder;
+
+    /// Result is ready to be sent to transport: text frame has Engine.IO message prefix `4`
+    fn encode_to_engine_payload(
+        &self,
+        message: &SocketIoMessage,
+    ) -> Result<SocketIoPayload, SocketIoEncodeError> {
+        let mut result = self.encode(message)?;
+
+        if !result.text_frame.is_empty() {
+            result.text_frame.insert(0, '4');
+        }
+
+        Ok(result)
+    }
+}
+
+/// Stateful decoder. Returns `None` while packet is not complete (for instance binary attachments are still expected)
+pub trait SocketIoPacketDecoder {
+    /// Text is a Socket.IO packet without Engine.IO message prefix `4`
+    fn decode_text(&mut self, text: &str) -> Result<Option<SocketIoMessage>, SocketIoDecodeError>;
+
+    fn decode_binary(
+        &mut self,
+        data: Vec<u8>,
+    ) -> Result<Option<SocketIoMessage>, SocketIoDecodeError>;
+}
+
+/// Default Socket.IO text format. Binary attachments are sent as separate frames after the text frame
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DefaultParser;
+
+impl SocketIoParser for DefaultParser {
+    type Decoder = DefaultPacketDecoder;
+
+    fn encode(&self, message: &SocketIoMessage) -> Result<SocketIoPayload, SocketIoEncodeError> {
+        let mut result = SocketIoPayload::new();
+        message.serialize(&mut result);
+        Ok(result)
+    }
+
+    fn create_decoder(&self, limits: SocketIoDecodeLimits) -> Self::Decoder {
+        DefaultPacketDecoder {
+            limits,
+            pending: None,
+        }
+    }
+}
+
+pub struct DefaultPacketDecoder {
+    limits: SocketIoDecodeLimits,
+    pending: Option<(SocketIoMessage, usize)>,
+}
+
+impl SocketIoPacketDecoder for DefaultPacketDecoder {
+    fn decode_text(&mut self, text: &str) -> Result<Option<SocketIoMessage>, SocketIoDecodeError> {
+        if self.pending.is_some() {
+            return Err(SocketIoDecodeError::InvalidPacket(
+                "Got text frame while reconstructing a binary packet".to_string(),
+            ));
+        }
+
+        self.limits.check_text_frame(text)?;
+
+        let attachments_count = read_binary_attachments_count(text).unwrap_or(0);
+        self.limits
+            .check_binary_attachments_count(attachments_count)?;
+
+        let message = SocketIoMessage::deserialize_with_limits(text, &self.limits)?;
+
+        if attachments_count == 0 {
+            return Ok(Some(message));
+        }
+
+        self.pending = Some((message, attachments_count));
+        Ok(None)
+    }
+
+    fn decode_binary(
+        &mut self,
+        data: Vec<u8>,
+    ) -> Result<Option<SocketIoMessage>, SocketIoDecodeError> {
+        self.limits.check_binary_frame(&data)?;
+
+        let (mut message, attachments_count) = match self.pending.take() {
+            Some(pending) => pending,
+            None => {
+                return Err(SocketIoDecodeError::InvalidPacket(
+                    "Got binary frame while not reconstructing a binary packet".to_string(),
+                ))
+            }
+        };
+
+        let attachments = message.get_attachments_mut().unwrap();
+        attachments.push(data);
+
+        if attachments.len() < attachments_count {
+            self.pending = Some((message, attachments_count));
+            return Ok(None);
+        }
+
+        Ok(Some(message))
+    }
+}
+
+/// Splits Engine.IO packets from Socket.IO messages and passes the latter to the parser decoder
+pub struct SocketIoContractDecoder<D: SocketIoPacketDecoder> {
+    decoder: D,
+    limits: SocketIoDecodeLimits,
+    raw_mode: bool,
+}
+
+impl<D: SocketIoPacketDecoder> SocketIoContractDecoder<D> {
+    pub fn new<P: SocketIoParser<Decoder = D>>(parser: &P, limits: SocketIoDecodeLimits) -> Self {
+        Self {
+            decoder: parser.create_decoder(limits.clone()),
+            limits,
+            raw_mode: false,
+        }
+    }
+
+    /// In raw mode Engine.IO messages are returned as [`SocketIoContract::EngineMessage`] without Socket.IO parsing
+    pub fn set_raw_mode(&mut self, value: bool) {
+        self.raw_mode = value;
+    }
+
+    pub fn is_raw_mode(&self) -> bool {
+        self.raw_mode
+    }
+
+    pub fn decode_text_frame(
+        &mut self,
+        frame: &str,
+    ) -> Result<Option<SocketIoContract>, SocketIoDecodeError> {
+        self.limits.check_text_frame(frame)?;
+        let packet = EngineIoPacket::deserialize(frame)?;
+        self.decode_packet(packet)
+    }
+
+    pub fn decode_binary_frame(
+        &mut self,
+        frame: Vec<u8>,
+    ) -> Result<Option<SocketIoContract>, SocketIoDecodeError> {
+        self.decode_packet(EngineIoPacket::from_binary(frame))
+    }
+
+    pub fn decode_packet(
+        &mut self,
+        packet: EngineIoPacket,
+    ) -> Result<Option<SocketIoContract>, SocketIoDecodeError> {
+        let result = match packet {
+            EngineIoPacket::Open(model) => SocketIoContract::Open(model),
+            EngineIoPacket::Close => SocketIoContract::Close,
+            EngineIoPacket::Ping { payload } => SocketIoContract::Ping { payload },
+            EngineIoPacket::Pong { payload } => SocketIoContract::Pong { payload },
+            EngineIoPacket::Message(message) => return self.decode_engine_message(message),
+            EngineIoPacket::Upgrade => SocketIoContract::Upgrade,
+            EngineIoPacket::Noop => SocketIoContract::Noop,
+        };
+
+        Ok(Some(result))
+    }
+
+    fn decode_engine_message(
+        &mut self,
+        message: EngineMessage,
+    ) -> Result<Option<SocketIoContract>, SocketIoDecodeError> {
+        if self.raw_mode {
+            match &message {
+                EngineMessage::Text(text) => self.limits.check_text_frame(text)?,
+                EngineMessage::Binary(data) => self.limits.check_binary_frame(data)?,
+            }
+
+            return Ok(Some(SocketIoContract::EngineMessage(message)));
+        }
+
+        let message = match message {
+            EngineMessage::Text(text) => {
+                self.limits.check_text_frame(&text)?;
+                self.decoder.decode_text(&text)?
+            }
+            EngineMessage::Binary(data) => self.decoder.decode_binary(data)?,
+        };
+
+        Ok(message.map(SocketIoContract::Message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_with_engine_prefix() {
+        let message = SocketIoMessage::Event {
+            namespace: "/".into(),
+            event_name: "foo".into(),
+            data: "".into(),
+            rest_args: Vec::new(),
+            ack: None,
+        };
+
+        let payload = DefaultParser.encode_to_engine_payload(&message).unwrap();
+
+        assert_eq!(payload.text_frame, r#"42["foo"]"#);
+        assert!(payload.binary_frames.is_empty());
+    }
+
+    #[test]
+    fn test_binary_event_is_reconstructed() {
+        let mut decoder = SocketIoContractDecoder::new(&DefaultParser, Default::default());
+
+        let result = decoder
+            .decode_text_frame(
+                r#"452-["file",{"_placeholder":true,"num":0},{"_placeholder":true,"num":1}]"#,
+            )
+            .unwrap();
+        assert!(result.is_none());
+
+        let result = decoder.decode_binary_frame(vec![1, 2]).unwrap();
+        assert!(result.is_none());
+
+        let result = decoder.decode_binary_frame(vec![3]).unwrap();
+
+        match result {
+            Some(SocketIoContract::Message(SocketIoMessage::BinaryEvent {
+                event_name,
+                attachments,
+                ..
+            })) => {
+                assert_eq!(event_name.as_str(), "file");
+                assert_eq!(attachments, vec![vec![1, 2], vec![3]]);
+            }
+            _ => panic!("BinaryEvent is expected"),
+        }
+    }
+
+    #[test]
+    fn test_unexpected_frames() {
+        let mut decoder = SocketIoContractDecoder::new(&DefaultParser, Default::default());
+
+        assert!(decoder.decode_binary_frame(vec![1]).is_err());
+
+        decoder
+            .decode_text_frame(r#"451-["file",{"_placeholder":true,"num":0}]"#)
+            .unwrap();
+
+        assert!(decoder.decode_text_frame(r#"42["foo"]"#).is_err());
+    }
+
+    #[test]
+    fn test_raw_mode() {
+        let mut decoder = SocketIoContractDecoder::new(&DefaultParser, Default::default());
+        decoder.set_raw_mode(true);
+
+        match decoder.decode_text_frame("4hello").unwrap() {
+            Some(SocketIoContract::EngineMessage(EngineMessage::Text(data))) => {
+                assert_eq!(data, "hello")
+            }
+            _ => panic!("EngineMessage is expected"),
+        }
+
+        match decoder.decode_binary_frame(vec![1, 2]).unwrap() {
+            Some(SocketIoContract::EngineMessage(EngineMessage::Binary(data))) => {
+                assert_eq!(data, vec![1, 2])
+            }
+            _ => panic!("EngineMessage is expected"),
+        }
+
+        match decoder.decode_text_frame("2probe").unwrap() {
+            Some(SocketIoContract::Ping { payload }) => assert_eq!(payload.unwrap(), "probe"),
+            _ => panic!("Ping is expected"),
+        }
+
+        match decoder.decode_text_frame("3").unwrap() {
+            Some(SocketIoContract::Pong { payload }) => assert!(payload.is_none()),
+            _ => panic!("Pong is expected"),
+        }
+    }
+}
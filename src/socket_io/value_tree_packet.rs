@@ -0,0 +1,453 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    join_args, split_args, SocketIoDecodeError, SocketIoDecodeLimits, SocketIoEncodeError,
+    SocketIoMessage,
+};
+
+pub(crate) const PACKET_TYPE_CONNECT: u64 = 0;
+pub(crate) const PACKET_TYPE_DISCONNECT: u64 = 1;
+pub(crate) const PACKET_TYPE_EVENT: u64 = 2;
+pub(crate) const PACKET_TYPE_ACK: u64 = 3;
+pub(crate) const PACKET_TYPE_CONNECT_ERROR: u64 = 4;
+pub(crate) const PACKET_TYPE_BINARY_EVENT: u64 = 5;
+pub(crate) const PACKET_TYPE_BINARY_ACK: u64 = 6;
+
+/// Value tree of a self-describing binary format. Packets are maps `{type, data, nsp, id}`,
+/// binary attachments are inline byte strings. Only serde backend differs between formats.
+pub(crate) trait PacketValue: Sized + Serialize + DeserializeOwned {
+    /// Packet types of events and acks with binary attachments
+    const BINARY_EVENT_TYPE: u64;
+    const BINARY_ACK_TYPE: u64;
+
+    fn from_u64(value: u64) -> Self;
+    fn from_i64(value: i64) -> Self;
+    fn from_bool(value: bool) -> Self;
+    fn from_text(value: &str) -> Self;
+    fn from_bytes(value: Vec<u8>) -> Self;
+    fn from_map(fields: Vec<(Self, Self)>) -> Self;
+
+    fn as_u64(&self) -> Option<u64>;
+    fn as_i64(&self) -> Option<i64>;
+    fn as_bool(&self) -> Option<bool>;
+    fn as_text(&self) -> Option<&str>;
+    fn as_map(&self) -> Option<&Vec<(Self, Self)>>;
+
+    fn as_bytes_mut(&mut self) -> Option<&mut Vec<u8>>;
+    fn as_array_mut(&mut self) -> Option<&mut Vec<Self>>;
+    fn as_map_mut(&mut self) -> Option<&mut Vec<(Self, Self)>>;
+
+    fn into_array(self) -> Option<Vec<Self>>;
+    fn into_map(self) -> Option<Vec<(Self, Self)>>;
+
+    fn write(&self) -> Vec<u8>;
+    fn read(src: &[u8]) -> Result<Self, String>;
+}
+
+pub(crate) fn encode_value_packet<V: PacketValue>(
+    message: &SocketIoMessage,
+) -> Result<Vec<u8>, SocketIoEncodeError> {
+    let mut fields = Vec::with_capacity(4);
+
+    match message {
+        SocketIoMessage::Connect {
+            namespace,
+            sid,
+            auth,
+        } => {
+            fields.push(field("type", V::from_u64(PACKET_TYPE_CONNECT)));
+
+            match (sid, auth) {
+                (Some(sid), _) => {
+                    let data = V::from_map(vec![field("sid", V::from_text(sid.as_str()))]);
+                    fields.push(field("data", data));
+                }
+                (None, Some(auth)) => {
+                    let data: V = serde_json::from_str(auth.as_str()).map_err(|err| {
+                        SocketIoEncodeError::InvalidEventData(format!(
+                            "Invalid auth [{}]. Error: {}",
+                            auth.as_str(),
+                            err
+                        ))
+                    })?;
+                    fields.push(field("data", data));
+                }
+                (None, None) => {}
+            }
+
+            fields.push(field("nsp", V::from_text(namespace.as_str())));
+        }
+        SocketIoMessage::Disconnect { namespace } => {
+            fields.push(field("type", V::from_u64(PACKET_TYPE_DISCONNECT)));
+            fields.push(field("nsp", V::from_text(namespace.as_str())));
+        }
+        SocketIoMessage::Event {
+            namespace,
+            event_name,
+            data,
+            rest_args,
+            ack,
+        } => {
+            fields.push(field("type", V::from_u64(PACKET_TYPE_EVENT)));
+            let data = encode_event_data::<V>(
+                event_name.as_str(),
+                &join_args(data.as_str(), rest_args),
+                &[],
+            )?;
+            fields.push(field("data", data));
+            fields.push(field("nsp", V::from_text(namespace.as_str())));
+
+            if let Some(ack) = ack {
+                fields.push(field("id", V::from_i64(*ack)));
+            }
+        }
+        SocketIoMessage::Ack {
+            namespace,
+            event_name,
+            data,
+            rest_args,
+            ack,
+        } => {
+            fields.push(field("type", V::from_u64(PACKET_TYPE_ACK)));
+            let data = encode_event_data::<V>(
+                event_name.as_str(),
+                &join_args(data.as_str(), rest_args),
+                &[],
+            )?;
+            fields.push(field("data", data));
+            fields.push(field("nsp", V::from_text(namespace.as_str())));
+            fields.push(field("id", V::from_i64(*ack)));
+        }
+        SocketIoMessage::ConnectError { namespace, message } => {
+            fields.push(field("type", V::from_u64(PACKET_TYPE_CONNECT_ERROR)));
+            let data = V::from_map(vec![field("message", V::from_text(message.as_str()))]);
+            fields.push(field("data", data));
+            fields.push(field("nsp", V::from_text(namespace.as_str())));
+        }
+        SocketIoMessage::BinaryEvent {
+            namespace,
+            event_name,
+            data,
+            rest_args,
+            ack,
+            attachments,
+        } => {
+            fields.push(field("type", V::from_u64(V::BINARY_EVENT_TYPE)));
+            let data = encode_event_data::<V>(
+                event_name.as_str(),
+                &join_args(data.as_str(), rest_args),
+                attachments,
+            )?;
+            fields.push(field("data", data));
+            fields.push(field("nsp", V::from_text(namespace.as_str())));
+
+            if let Some(ack) = ack {
+                fields.push(field("id", V::from_i64(*ack)));
+            }
+        }
+        SocketIoMessage::BinaryAck {
+            namespace,
+            event_name,
+            data,
+            rest_args,
+            ack,
+            attachments,
+        } => {
+            fields.push(field("type", V::from_u64(V::BINARY_ACK_TYPE)));
+            let data = encode_event_data::<V>(
+                event_name.as_str(),
+                &join_args(data.as_str(), rest_args),
+                attachments,
+            )?;
+            fields.push(field("data", data));
+            fields.push(field("nsp", V::from_text(namespace.as_str())));
+            fields.push(field("id", V::from_i64(*ack)));
+        }
+    }
+
+    Ok(V::from_map(fields).write())
+}
+
+/// Event data limits are checked while walking the value tree, before any of it is converted to JSON
+pub(crate) fn decode_value_packet<V: PacketValue>(
+    src: &[u8],
+    limits: &SocketIoDecodeLimits,
+) -> Result<SocketIoMessage, SocketIoDecodeError> {
+    let fields = V::read(src)
+        .map_err(invalid_packet)?
+        .into_map()
+        .ok_or_else(|| invalid_packet("Packet must be a map".to_string()))?;
+
+    let mut packet_type = None;
+    let mut namespace = None;
+    let mut data = None;
+    let mut id = None;
+
+    for (key, value) in fields {
+        match key.as_text() {
+            Some("type") => packet_type = value.as_u64(),
+            Some("nsp") => namespace = value.as_text().map(|nsp| nsp.to_string()),
+            Some("data") => data = Some(value),
+            Some("id") => id = value.as_i64(),
+            _ => {}
+        }
+    }
+
+    let packet_type = packet_type.ok_or_else(|| invalid_packet("Invalid type".to_string()))?;
+    let namespace = namespace.ok_or_else(|| invalid_packet("Invalid nsp".to_string()))?;
+
+    match packet_type {
+        PACKET_TYPE_CONNECT => {
+            let sid = data
+                .as_ref()
+                .and_then(|data| get_map_field(data, "sid"))
+                .and_then(|sid| sid.as_text())
+                .map(|sid| sid.to_string().into());
+
+            let auth = match (&sid, &data) {
+                (None, Some(data)) => Some(
+                    serde_json::to_string(data)
+                        .map_err(|err| invalid_packet(err.to_string()))?
+                        .into(),
+                ),
+                _ => None,
+            };
+
+            Ok(SocketIoMessage::Connect {
+                namespace: namespace.into(),
+                sid,
+                auth,
+            })
+        }
+        PACKET_TYPE_DISCONNECT => Ok(SocketIoMessage::Disconnect {
+            namespace: namespace.into(),
+        }),
+        PACKET_TYPE_CONNECT_ERROR => {
+            let message = data.as_ref().and_then(|data| {
+                data.as_text()
+                    .or_else(|| get_map_field(data, "message")?.as_text())
+                    .map(|message| message.to_string())
+            });
+
+            Ok(SocketIoMessage::ConnectError {
+                namespace: namespace.into(),
+                message: message
+                    .unwrap_or_else(|| "Unknown (no message found in data)".to_string())
+                    .into(),
+            })
+        }
+        PACKET_TYPE_EVENT | PACKET_TYPE_BINARY_EVENT => {
+            let (event_name, args, attachments) = decode_event_data(data, false, limits)?;
+            let (data, rest_args) = split_args(args);
+
+            if attachments.is_empty() {
+                return Ok(SocketIoMessage::Event {
+                    namespace: namespace.into(),
+                    event_name: event_name.into(),
+                    data,
+                    rest_args,
+                    ack: id,
+                });
+            }
+
+            Ok(SocketIoMessage::BinaryEvent {
+                namespace: namespace.into(),
+                event_name: event_name.into(),
+                data,
+                rest_args,
+                ack: id,
+                attachments,
+            })
+        }
+        PACKET_TYPE_ACK | PACKET_TYPE_BINARY_ACK => {
+            let ack = id.ok_or_else(|| invalid_packet("Ack id is missing".to_string()))?;
+            let (event_name, args, attachments) = decode_event_data(data, true, limits)?;
+            let (data, rest_args) = split_args(args);
+
+            if attachments.is_empty() {
+                return Ok(SocketIoMessage::Ack {
+                    namespace: namespace.into(),
+                    event_name: event_name.into(),
+                    data,
+                    rest_args,
+                    ack,
+                });
+            }
+
+            Ok(SocketIoMessage::BinaryAck {
+                namespace: namespace.into(),
+                event_name: event_name.into(),
+                data,
+                rest_args,
+                ack,
+                attachments,
+            })
+        }
+        _ => Err(invalid_packet(format!(
+            "Unknown packet type {}",
+            packet_type
+        ))),
+    }
+}
+
+fn field<V: PacketValue>(name: &'static str, value: V) -> (V, V) {
+    (V::from_text(name), value)
+}
+
+fn invalid_packet(message: String) -> SocketIoDecodeError {
+    SocketIoDecodeError::InvalidPacket(message)
+}
+
+fn get_map_field<'s, V: PacketValue>(value: &'s V, name: &str) -> Option<&'s V> {
+    value
+        .as_map()?
+        .iter()
+        .find(|(key, _)| key.as_text() == Some(name))
+        .map(|(_, value)| value)
+}
+
+fn encode_event_data<V: PacketValue>(
+    event_name: &str,
+    data: &str,
+    attachments: &[Vec<u8>],
+) -> Result<V, SocketIoEncodeError> {
+    let mut json = String::from("[");
+
+    // Acks may have no leading string argument
+    if !event_name.is_empty() {
+        json.push_str(&serde_json::to_string(event_name).unwrap());
+    }
+
+    if !data.is_empty() {
+        if !event_name.is_empty() {
+            json.push(',');
+        }
+
+        json.push_str(data);
+    }
+
+    json.push(']');
+
+    let mut result: V = serde_json::from_str(&json).map_err(|err| {
+        SocketIoEncodeError::InvalidEventData(format!(
+            "Invalid event data [{}]. Error: {}",
+            data, err
+        ))
+    })?;
+
+    replace_placeholders(&mut result, attachments);
+    Ok(result)
+}
+
+fn replace_placeholders<V: PacketValue>(value: &mut V, attachments: &[Vec<u8>]) {
+    if let Some(num) = get_placeholder_num(value) {
+        if let Some(attachment) = attachments.get(num) {
+            *value = V::from_bytes(attachment.clone());
+            return;
+        }
+    }
+
+    if let Some(items) = value.as_array_mut() {
+        for item in items {
+            replace_placeholders(item, attachments);
+        }
+    } else if let Some(fields) = value.as_map_mut() {
+        for (_, item) in fields {
+            replace_placeholders(item, attachments);
+        }
+    }
+}
+
+fn get_placeholder_num<V: PacketValue>(value: &V) -> Option<usize> {
+    if get_map_field(value, "_placeholder")?.as_bool()? {
+        return Some(get_map_field(value, "num")?.as_u64()? as usize);
+    }
+
+    None
+}
+
+/// `depth` is the number of arrays and maps around the value, event data array included
+fn extract_binaries<V: PacketValue>(
+    value: &mut V,
+    attachments: &mut Vec<Vec<u8>>,
+    depth: usize,
+    limits: &SocketIoDecodeLimits,
+) -> Result<(), SocketIoDecodeError> {
+    if let Some(data) = value.as_bytes_mut() {
+        attachments.push(std::mem::take(data));
+        limits.check_binary_attachments_count(attachments.len())?;
+
+        *value = V::from_map(vec![
+            field("_placeholder", V::from_bool(true)),
+            field("num", V::from_u64(attachments.len() as u64 - 1)),
+        ]);
+
+        return Ok(());
+    }
+
+    let is_container = value.as_array_mut().is_some() || value.as_map_mut().is_some();
+
+    if is_container && depth + 1 > limits.max_json_depth {
+        return Err(SocketIoDecodeError::JsonTooDeep {
+            max_depth: limits.max_json_depth,
+        });
+    }
+
+    if let Some(items) = value.as_array_mut() {
+        for item in items {
+            extract_binaries(item, attachments, depth + 1, limits)?;
+        }
+    } else if let Some(fields) = value.as_map_mut() {
+        for (_, item) in fields {
+            extract_binaries(item, attachments, depth + 1, limits)?;
+        }
+    }
+
+    Ok(())
+}
+
+type EventNameArgsAndAttachments = (String, Vec<String>, Vec<Vec<u8>>);
+
+/// Ack arguments are not required to start with a string, in that case event name is empty
+fn decode_event_data<V: PacketValue>(
+    data: Option<V>,
+    is_ack: bool,
+    limits: &SocketIoDecodeLimits,
+) -> Result<EventNameArgsAndAttachments, SocketIoDecodeError> {
+    let items = data
+        .and_then(|data| data.into_array())
+        .ok_or_else(|| invalid_packet("Event data must be an array".to_string()))?;
+
+    // Event name is counted the same way as in text frames
+    if items.len() > limits.max_event_arguments + 1 {
+        return Err(SocketIoDecodeError::TooManyArguments {
+            max_count: limits.max_event_arguments,
+        });
+    }
+
+    let mut items = items.into_iter().peekable();
+
+    let event_name = match items.peek().and_then(|item| item.as_text()) {
+        Some(event_name) => {
+            let event_name = event_name.to_string();
+            items.next();
+            event_name
+        }
+        None if is_ack => String::new(),
+        None => return Err(invalid_packet("Event name must be String".to_string())),
+    };
+
+    let mut attachments = Vec::new();
+    let mut args = Vec::new();
+
+    for mut item in items {
+        extract_binaries(&mut item, &mut attachments, 1, limits)?;
+
+        let json = serde_json::to_string(&item)
+            .map_err(|err| invalid_packet(format!("Can not convert data to json: {}", err)))?;
+
+        args.push(json);
+    }
+
+    Ok((event_name, args, attachments))
+}
@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_tungstenite::WebSocketStream;
+
+use crate::{
+    DefaultParser, EngineIoWebSocket, EngineIoWebSocketError, OutgoingPayload, SocketIoContract,
+    SocketIoContractDecoder, SocketIoDecodeError, SocketIoDecodeLimits, SocketIoEncodeError,
+    SocketIoMessage, SocketIoParser, SocketIoPayload,
+};
+
+#[derive(Debug)]
+pub enum WebSocketTransportError {
+    WebSocket(tokio_tungstenite::tungstenite::Error),
+    Decode(SocketIoDecodeError),
+    Encode(SocketIoEncodeError),
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for WebSocketTransportError {
+    fn from(value: tokio_tungstenite::tungstenite::Error) -> Self {
+        Self::WebSocket(value)
+    }
+}
+
+impl From<SocketIoDecodeError> for WebSocketTransportError {
+    fn from(value: SocketIoDecodeError) -> Self {
+        Self::Decode(value)
+    }
+}
+
+impl From<SocketIoEncodeError> for WebSocketTransportError {
+    fn from(value: SocketIoEncodeError) -> Self {
+        Self::Encode(value)
+    }
+}
+
+impl From<EngineIoWebSocketError> for WebSocketTransportError {
+    fn from(value: EngineIoWebSocketError) -> Self {
+        match value {
+            EngineIoWebSocketError::WebSocket(err) => Self::WebSocket(err),
+            EngineIoWebSocketError::Decode(err) => Self::Decode(err.into()),
+        }
+    }
+}
+
+/// Socket.IO on top of [`EngineIoWebSocket`]
+pub struct WebSocketTransport<S, P: SocketIoParser = DefaultParser> {
+    engine: EngineIoWebSocket<S>,
+    parser: P,
+    limits: SocketIoDecodeLimits,
+    decoder: SocketIoContractDecoder<P::Decoder>,
+    query: HashMap<String, String>,
+    headers: HashMap<String, String>,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> WebSocketTransport<S, DefaultParser> {
+    pub fn new(stream: WebSocketStream<S>) -> Self {
+        Self::new_with_parser(stream, DefaultParser)
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin, P: SocketIoParser> WebSocketTransport<S, P> {
+    pub fn new_with_parser(stream: WebSocketStream<S>, parser: P) -> Self {
+        let limits = SocketIoDecodeLimits::default();
+
+        Self {
+            engine: EngineIoWebSocket::new(stream),
+            decoder: SocketIoContractDecoder::new(&parser, limits.clone()),
+            parser,
+            limits,
+            query: HashMap::new(),
+            headers: HashMap::new(),
+        }
+    }
+
+    /// Incoming frames exceeding limits are rejected with [`WebSocketTransportError::Decode`]
+    pub fn with_limits(mut self, limits: SocketIoDecodeLimits) -> Self {
+        let raw_mode = self.decoder.is_raw_mode();
+        self.decoder = SocketIoContractDecoder::new(&self.parser, limits.clone());
+        self.decoder.set_raw_mode(raw_mode);
+        self.limits = limits;
+        self
+    }
+
+    /// Engine.IO messages are received as [`SocketIoContract::EngineMessage`] without Socket.IO parsing
+    pub fn with_raw_mode(mut self) -> Self {
+        self.decoder.set_raw_mode(true);
+        self
+    }
+
+    /// Query and headers of the upgrade request, e.g. collected with `tokio_tungstenite::accept_hdr_async`
+    pub fn with_request(
+        mut self,
+        query: HashMap<String, String>,
+        headers: HashMap<String, String>,
+    ) -> Self {
+        self.query = query;
+        self.headers = headers;
+        self
+    }
+
+    pub fn get_query(&self) -> &HashMap<String, String> {
+        &self.query
+    }
+
+    pub fn get_headers(&self) -> &HashMap<String, String> {
+        &self.headers
+    }
+
+    pub fn get_limits(&self) -> &SocketIoDecodeLimits {
+        &self.limits
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.engine.is_closed()
+    }
+
+    /// Returns next decoded contract. Binary packets are returned once all attachments are received.
+    /// `None` means connection is closed.
+    pub async fn receive(&mut self) -> Result<Option<SocketIoContract>, WebSocketTransportError> {
+        while let Some(packet) = self.engine.receive().await? {
+            if let Some(contract) = self.decoder.decode_packet(packet)? {
+                return Ok(Some(contract));
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub async fn send(&mut self, payload: &SocketIoPayload) -> Result<(), WebSocketTransportError> {
+        self.engine.send_frames(payload.to_engine_frames()).await?;
+        Ok(())
+    }
+
+    /// `compress` flag would control permessage-deflate, but tungstenite does not negotiate the extension,
+    /// so frames are always sent uncompressed
+    pub async fn send_outgoing(
+        &mut self,
+        outgoing: &OutgoingPayload,
+    ) -> Result<(), WebSocketTransportError> {
+        self.send(&outgoing.payload).await
+    }
+
+    pub async fn send_contract(
+        &mut self,
+        contract: &SocketIoContract,
+    ) -> Result<(), WebSocketTransportError> {
+        let payload = contract.serialize();
+        self.send(&payload).await
+    }
+
+    pub async fn send_message(
+        &mut self,
+        message: &SocketIoMessage,
+    ) -> Result<(), WebSocketTransportError> {
+        let payload = self.parser.encode_to_engine_payload(message)?;
+        self.send(&payload).await
+    }
+
+    pub async fn close(&mut self) -> Result<(), WebSocketTransportError> {
+        self.engine.close().await?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> WebSocketStream<S> {
+        self.engine.into_inner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::{TcpListener, TcpStream};
+
+    use super::*;
+    use crate::SocketIoHandshakeOpenModel;
+
+    async fn connect_loopback() -> (
+        WebSocketStream<TcpStream>,
+        tokio::task::JoinHandle<WebSocketStream<TcpStream>>,
+    ) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (tcp_stream, _) = listener.accept().await.unwrap();
+            tokio_tungstenite::accept_async(tcp_stream).await.unwrap()
+        });
+
+        let tcp_stream = TcpStream::connect(addr).await.unwrap();
+        let url = format!("ws://{}/socket.io/?EIO=4&transport=websocket", addr);
+        let (ws_stream, _) = tokio_tungstenite::client_async(url, tcp_stream)
+            .await
+            .unwrap();
+
+        (ws_stream, server)
+    }
+
+    #[tokio::test]
+    async fn test_loopback_send_and_receive() {
+        let (client_stream, server) = connect_loopback().await;
+
+        let server = tokio::spawn(async move {
+            let mut transport = WebSocketTransport::new(server.await.unwrap());
+
+            let open = SocketIoContract::Open(SocketIoHandshakeOpenModel {
+                sid: "test-sid".to_string(),
+                upgrades: vec![],
+                ping_interval: 25000,
+                ping_timeout: 20000,
+                max_payload: Some(1000000),
+                extra: Default::default(),
+            });
+            transport.send_contract(&open).await.unwrap();
+
+            let message = SocketIoMessage::BinaryEvent {
+                namespace: "/".into(),
+                event_name: "file".into(),
+                data: r#"{"_placeholder":true,"num":0},{"_placeholder":true,"num":1}"#.into(),
+                rest_args: Vec::new(),
+                ack: None,
+                attachments: vec![vec![1, 2, 3], vec![4, 5]],
+            };
+            transport.send_message(&message).await.unwrap();
+
+            match transport.receive().await.unwrap() {
+                Some(SocketIoContract::Ping { payload }) => {
+                    assert!(payload.is_none());
+                }
+                _ => panic!("Ping is expected"),
+            }
+
+            assert!(transport.receive().await.unwrap().is_none());
+            assert!(transport.is_closed());
+        });
+
+        let mut transport = WebSocketTransport::new(client_stream);
+
+        match transport.receive().await.unwrap() {
+            Some(SocketIoContract::Open(model)) => {
+                assert_eq!(model.sid, "test-sid");
+                assert_eq!(model.ping_interval, 25000);
+            }
+            _ => panic!("Open is expected"),
+        }
+
+        match transport.receive().await.unwrap() {
+            Some(SocketIoContract::Message(SocketIoMessage::BinaryEvent {
+                event_name,
+                attachments,
+                ..
+            })) => {
+                assert_eq!(event_name.as_str(), "file");
+                assert_eq!(attachments, vec![vec![1, 2, 3], vec![4, 5]]);
+            }
+            _ => panic!("BinaryEvent is expected"),
+        }
+
+        transport
+            .send_contract(&SocketIoContract::Ping { payload: None })
+            .await
+            .unwrap();
+
+        transport.close().await.unwrap();
+
+        server.await.unwrap();
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[tokio::test]
+    async fn test_loopback_with_msgpack_parser() {
+        let (client_stream, server) = connect_loopback().await;
+
+        let server = tokio::spawn(async move {
+            let mut transport =
+                WebSocketTransport::new_with_parser(server.await.unwrap(), crate::MsgPackParser);
+
+            let message = SocketIoMessage::Event {
+                namespace: "/".into(),
+                event_name: "foo".into(),
+                data: r#"{"a":1}"#.into(),
+                rest_args: Vec::new(),
+                ack: Some(1),
+            };
+            transport.send_message(&message).await.unwrap();
+        });
+
+        let mut transport =
+            WebSocketTransport::new_with_parser(client_stream, crate::MsgPackParser);
+
+        match transport.receive().await.unwrap() {
+            Some(SocketIoContract::Message(SocketIoMessage::Event {
+                event_name,
+                data,
+                ack,
+                ..
+            })) => {
+                assert_eq!(event_name.as_str(), "foo");
+                assert_eq!(data.as_str(), r#"{"a":1}"#);
+                assert_eq!(ack, Some(1));
+            }
+            _ => panic!("Event is expected"),
+        }
+
+        server.await.unwrap();
+    }
+}
@@ -4,4 +4,17 @@ pub struct SocketIoSettings {
     pub ping_interval: Duration,
     pub ping_timeout: Duration,
     pub max_payload_size: usize,
+    /// Transports announced in the handshake of polling sessions. WebSocket sessions always get an empty list
+    pub upgrades: Vec<String>,
+}
+
+impl Default for SocketIoSettings {
+    fn default() -> Self {
+        Self {
+            ping_interval: Duration::from_secs(25),
+            ping_timeout: Duration::from_secs(20),
+            max_payload_size: 1_000_000,
+            upgrades: vec![String::from("websocket")],
+        }
+    }
 }